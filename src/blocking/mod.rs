@@ -51,10 +51,12 @@ pub(crate) mod providers;
 use std::sync::mpsc::RecvTimeoutError;
 use std::sync::mpsc::SyncSender;
 use std::sync::{mpsc, Arc, LazyLock, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
+// Every provider is behind its own feature, so there may be none to use
+#[allow(unused_imports)]
 use crate::blocking::providers::*;
 use crate::{ProviderId, DEFAULT_DETECTION_TIMEOUT};
 
@@ -63,13 +65,20 @@ use crate::{ProviderId, DEFAULT_DETECTION_TIMEOUT};
 pub(crate) trait Provider: Send + Sync {
     fn identifier(&self) -> ProviderId;
     fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration);
+
+    /// Tries to identify the provider using only local checks (vendor files and environment variables).
+    ///
+    /// Providers that can only be identified via their metadata server leave this as `false`.
+    fn identify_offline(&self) -> bool {
+        false
+    }
 }
 
 type P = Arc<dyn Provider>;
 
 static PROVIDERS: LazyLock<Mutex<Vec<P>>> = LazyLock::new(|| {
     Mutex::new(vec![
-        #[cfg(feature = "akami")]
+        #[cfg(feature = "akamai")]
         {
             Arc::new(akamai::Akamai) as P
         },
@@ -85,14 +94,38 @@ static PROVIDERS: LazyLock<Mutex<Vec<P>>> = LazyLock::new(|| {
         {
             Arc::new(azure::Azure) as P
         },
+        #[cfg(feature = "civo")]
+        {
+            Arc::new(civo::Civo) as P
+        },
         #[cfg(feature = "digitalocean")]
         {
             Arc::new(digitalocean::DigitalOcean) as P
         },
+        #[cfg(feature = "equinix")]
+        {
+            Arc::new(equinix::EquinixMetal) as P
+        },
+        #[cfg(feature = "exoscale")]
+        {
+            Arc::new(exoscale::Exoscale) as P
+        },
+        #[cfg(feature = "fly")]
+        {
+            Arc::new(fly::FlyIo) as P
+        },
         #[cfg(feature = "gcp")]
         {
             Arc::new(gcp::Gcp) as P
         },
+        #[cfg(feature = "ibm")]
+        {
+            Arc::new(ibm::Ibm) as P
+        },
+        #[cfg(feature = "nutanix")]
+        {
+            Arc::new(nutanix::Nutanix) as P
+        },
         #[cfg(feature = "oci")]
         {
             Arc::new(oci::Oci) as P
@@ -101,6 +134,22 @@ static PROVIDERS: LazyLock<Mutex<Vec<P>>> = LazyLock::new(|| {
         {
             Arc::new(openstack::OpenStack) as P
         },
+        #[cfg(feature = "ovh")]
+        {
+            Arc::new(ovh::Ovh) as P
+        },
+        #[cfg(feature = "render")]
+        {
+            Arc::new(render::Render) as P
+        },
+        #[cfg(feature = "upcloud")]
+        {
+            Arc::new(upcloud::UpCloud) as P
+        },
+        #[cfg(feature = "vmware")]
+        {
+            Arc::new(vmware::VMware) as P
+        },
         #[cfg(feature = "vultr")]
         {
             Arc::new(vultr::Vultr) as P
@@ -163,11 +212,106 @@ pub fn supported_providers() -> Result<Vec<String>> {
 /// ```
 pub fn detect(timeout: Option<Duration>) -> Result<ProviderId> {
     let timeout = timeout.unwrap_or(DEFAULT_DETECTION_TIMEOUT);
-    let (tx, rx) = mpsc::sync_channel::<ProviderId>(1);
+
+    Ok(identify_first(providers()?, timeout)?.unwrap_or_default())
+}
+
+/// Detects the host's cloud provider with a timeout, returning `None` if all operations timed out.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cloud_detect::blocking::detect_with_timeout;
+///
+/// match detect_with_timeout(Duration::from_secs(1)).unwrap() {
+///     Some(provider) => println!("Detected provider: {:?}", provider),
+///     None => println!("Detection timed out"),
+/// }
+/// ```
+pub fn detect_with_timeout(duration: Duration) -> Result<Option<ProviderId>> {
+    identify_first(providers()?, duration)
+}
+
+/// Returns whether the host runs in any cloud, i.e. whether [detect] would identify anything other than
+/// [ProviderId::Unknown].
+///
+/// # Arguments
+///
+/// * `timeout` - Maximum time allowed for detection. Defaults to [DEFAULT_DETECTION_TIMEOUT](constant.DEFAULT_DETECTION_TIMEOUT.html) if `None`.
+///
+/// # Examples
+///
+/// ```
+/// use cloud_detect::blocking::is_cloud;
+///
+/// if is_cloud(None).unwrap() {
+///     println!("Running in a cloud");
+/// }
+/// ```
+pub fn is_cloud(timeout: Option<Duration>) -> Result<bool> {
+    let timeout = timeout.unwrap_or(DEFAULT_DETECTION_TIMEOUT);
+
+    is_cloud_from(providers()?, timeout)
+}
+
+/// Detects all cloud providers that match the host.
+///
+/// Unlike [detect], which returns as soon as any provider is identified, this waits for every provider to finish (or
+/// for the timeout to elapse) and returns every match.
+///
+/// # Arguments
+///
+/// * `timeout` - Maximum time allowed for detection. Defaults to [DEFAULT_DETECTION_TIMEOUT](constant.DEFAULT_DETECTION_TIMEOUT.html) if `None`.
+///   Providers that have not finished by then are not included in the result.
+///
+/// # Examples
+///
+/// ```
+/// use cloud_detect::blocking::detect_all;
+///
+/// let providers = detect_all(None).unwrap();
+/// println!("Detected providers: {:?}", providers);
+/// ```
+pub fn detect_all(timeout: Option<Duration>) -> Result<Vec<ProviderId>> {
+    let timeout = timeout.unwrap_or(DEFAULT_DETECTION_TIMEOUT);
+
+    Ok(identify_all(providers()?, timeout))
+}
+
+/// Detects the host's cloud provider using only local vendor files and environment variables, without making any network
+/// requests.
+///
+/// The checks run sequentially on the calling thread, as they only read local files, so this neither spawns threads
+/// nor needs a timeout. This makes it a cheap way to detect the provider at startup, e.g. before an async runtime is
+/// built. Providers that can only be identified via their metadata server (e.g. Akamai) are never detected in this
+/// mode.
+///
+/// # Examples
+///
+/// ```
+/// use cloud_detect::blocking::detect_offline;
+///
+/// let provider = detect_offline().unwrap();
+/// println!("Detected provider: {:?}", provider);
+/// ```
+pub fn detect_offline() -> Result<ProviderId> {
+    Ok(identify_offline(providers()?).unwrap_or_default())
+}
+
+/// Returns a snapshot of the registered providers.
+fn providers() -> Result<Vec<P>> {
     let guard = PROVIDERS
         .lock()
         .map_err(|_| anyhow::anyhow!("Error locking providers"))?;
-    let provider_entries: Vec<P> = guard.iter().cloned().collect();
+
+    Ok(guard.iter().cloned().collect())
+}
+
+/// Identifies the first provider to match, returning `None` if none matched within the timeout.
+fn identify_first(provider_entries: Vec<P>, timeout: Duration) -> Result<Option<ProviderId>> {
+    let (tx, rx) = mpsc::sync_channel::<ProviderId>(1);
 
     for provider in provider_entries {
         let tx = tx.clone();
@@ -175,34 +319,181 @@ pub fn detect(timeout: Option<Duration>) -> Result<ProviderId> {
     }
 
     match rx.recv_timeout(timeout) {
-        Ok(provider_id) => Ok(provider_id),
+        Ok(provider_id) => Ok(Some(provider_id)),
         Err(err) => match err {
-            RecvTimeoutError::Timeout => Ok(ProviderId::Unknown),
+            RecvTimeoutError::Timeout => Ok(None),
             RecvTimeoutError::Disconnected => Err(anyhow::anyhow!("Error receiving message")),
         },
     }
 }
 
+/// Identifies the first provider to match using only local checks.
+fn is_cloud_from(provider_entries: Vec<P>, timeout: Duration) -> Result<bool> {
+    Ok(identify_first(provider_entries, timeout)?.is_some_and(|id| id != ProviderId::Unknown))
+}
+
+fn identify_offline(provider_entries: Vec<P>) -> Option<ProviderId> {
+    provider_entries
+        .iter()
+        .find(|provider| provider.identify_offline())
+        .map(|provider| provider.identifier())
+}
+
+/// Identifies every provider that matches within the timeout.
+fn identify_all(provider_entries: Vec<P>, timeout: Duration) -> Vec<ProviderId> {
+    let deadline = Instant::now() + timeout;
+    // Every provider may match, so make room for one result from each to avoid blocking on send
+    let (tx, rx) = mpsc::sync_channel::<ProviderId>(provider_entries.len());
+
+    for provider in provider_entries {
+        let tx = tx.clone();
+        std::thread::spawn(move || provider.identify(tx, timeout));
+    }
+
+    // Drop our own sender so that the channel disconnects once every provider has finished
+    drop(tx);
+
+    let mut provider_ids = Vec::new();
+    loop {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(provider_id) => provider_ids.push(provider_id),
+            Err(RecvTimeoutError::Timeout) => {
+                tracing::trace!("Timed out waiting for all providers to finish identifying");
+                break;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    provider_ids
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
 
     use super::*;
 
+    struct TestProvider {
+        id: ProviderId,
+        matches: bool,
+        delay: Duration,
+    }
+
+    impl Provider for TestProvider {
+        fn identifier(&self) -> ProviderId {
+            self.id.clone()
+        }
+
+        fn identify(&self, tx: SyncSender<ProviderId>, _timeout: Duration) {
+            std::thread::sleep(self.delay);
+            if self.matches {
+                let _ = tx.send(self.id.clone());
+            }
+        }
+
+        fn identify_offline(&self) -> bool {
+            self.matches
+        }
+    }
+
+    fn test_provider(id: ProviderId, matches: bool, delay: Duration) -> P {
+        Arc::new(TestProvider { id, matches, delay })
+    }
+
     #[test]
     fn test_supported_providers() -> Result<()> {
         let providers = supported_providers()?;
-        assert_eq!(providers.len(), 9);
+        assert_eq!(providers.len(), 19);
         assert!(providers.contains(&akamai::IDENTIFIER.to_string()));
         assert!(providers.contains(&alibaba::IDENTIFIER.to_string()));
         assert!(providers.contains(&aws::IDENTIFIER.to_string()));
         assert!(providers.contains(&azure::IDENTIFIER.to_string()));
+        assert!(providers.contains(&civo::IDENTIFIER.to_string()));
         assert!(providers.contains(&digitalocean::IDENTIFIER.to_string()));
+        assert!(providers.contains(&equinix::IDENTIFIER.to_string()));
+        assert!(providers.contains(&exoscale::IDENTIFIER.to_string()));
+        assert!(providers.contains(&fly::IDENTIFIER.to_string()));
         assert!(providers.contains(&gcp::IDENTIFIER.to_string()));
+        assert!(providers.contains(&ibm::IDENTIFIER.to_string()));
+        assert!(providers.contains(&nutanix::IDENTIFIER.to_string()));
         assert!(providers.contains(&oci::IDENTIFIER.to_string()));
         assert!(providers.contains(&openstack::IDENTIFIER.to_string()));
+        assert!(providers.contains(&ovh::IDENTIFIER.to_string()));
+        assert!(providers.contains(&render::IDENTIFIER.to_string()));
+        assert!(providers.contains(&upcloud::IDENTIFIER.to_string()));
+        assert!(providers.contains(&vmware::IDENTIFIER.to_string()));
         assert!(providers.contains(&vultr::IDENTIFIER.to_string()));
 
         Ok(())
     }
+
+    #[test]
+    fn test_identify_first() -> Result<()> {
+        let providers = vec![
+            test_provider(ProviderId::AWS, false, Duration::ZERO),
+            test_provider(ProviderId::GCP, true, Duration::ZERO),
+        ];
+        assert_eq!(
+            identify_first(providers, Duration::from_secs(1))?,
+            Some(ProviderId::GCP)
+        );
+
+        let providers = vec![test_provider(ProviderId::AWS, true, Duration::from_secs(1))];
+        assert_eq!(identify_first(providers, Duration::from_millis(50))?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_cloud_from() -> Result<()> {
+        let providers = vec![
+            test_provider(ProviderId::AWS, true, Duration::ZERO),
+            test_provider(ProviderId::GCP, false, Duration::ZERO),
+        ];
+        assert!(is_cloud_from(providers, Duration::from_secs(1))?);
+
+        let providers = vec![test_provider(ProviderId::GCP, false, Duration::ZERO)];
+        assert!(!is_cloud_from(providers, Duration::from_secs(1))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identify_all() {
+        let providers = vec![
+            test_provider(ProviderId::AWS, true, Duration::ZERO),
+            test_provider(ProviderId::Azure, false, Duration::ZERO),
+            test_provider(ProviderId::GCP, true, Duration::from_millis(10)),
+            test_provider(ProviderId::Vultr, true, Duration::from_secs(1)),
+        ];
+        let provider_ids = identify_all(providers, Duration::from_millis(500));
+
+        assert_eq!(provider_ids.len(), 2);
+        assert!(provider_ids.contains(&ProviderId::AWS));
+        assert!(provider_ids.contains(&ProviderId::GCP));
+    }
+
+    #[test]
+    fn test_identify_offline() {
+        let providers = vec![
+            test_provider(ProviderId::AWS, false, Duration::ZERO),
+            test_provider(ProviderId::GCP, true, Duration::ZERO),
+            test_provider(ProviderId::Vultr, true, Duration::ZERO),
+        ];
+        assert_eq!(identify_offline(providers), Some(ProviderId::GCP));
+
+        let providers = vec![test_provider(ProviderId::AWS, false, Duration::ZERO)];
+        assert_eq!(identify_offline(providers), None);
+    }
+
+    #[test]
+    fn test_detect_without_runtime() -> Result<()> {
+        // These must work from a plain thread, without a surrounding async runtime
+        detect_all(Some(Duration::from_millis(100)))?;
+        detect_with_timeout(Duration::from_millis(100))?;
+        detect_offline()?;
+
+        Ok(())
+    }
 }
@@ -3,10 +3,9 @@
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
@@ -46,7 +45,7 @@ impl Akamai {
         let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
         tracing::trace!("Retrieving {} token from: {}", IDENTIFIER, token_url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
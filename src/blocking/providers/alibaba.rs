@@ -1,15 +1,20 @@
 //! Alibaba Cloud.
+//!
+//! As in the async provider, a metadata token is requested first for instances in hardened mode.
 
 use std::fs;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://100.100.100.200";
-const METADATA_PATH: &str = "/latest/meta-data/latest/meta-data/instance/virtualization-solution";
+const METADATA_PATH: &str = "/latest/meta-data/instance/virtualization-solution";
+const METADATA_TOKEN_PATH: &str = "/latest/api/token";
+const TOKEN_HEADER: &str = "X-aliyun-ecs-metadata-token";
+const TOKEN_TTL_HEADER: &str = "X-aliyun-ecs-metadata-token-ttl-seconds";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/product_name";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::Alibaba;
 
@@ -31,6 +36,11 @@ impl Provider for Alibaba {
             }
         }
     }
+
+    /// Tries to identify Alibaba Cloud without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE)
+    }
 }
 
 impl Alibaba {
@@ -39,17 +49,19 @@ impl Alibaba {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = reqwest::blocking::Client::builder()
-            .timeout(timeout)
-            .build()
-        {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
             return false;
         };
 
-        match client.get(url).send() {
+        let req = match self.get_token(&client, metadata_uri) {
+            Some(token) => client.get(url).header(TOKEN_HEADER, token),
+            None => client.get(url),
+        };
+
+        match req.send() {
             Ok(resp) => match resp.text() {
                 Ok(text) => text.contains("ECS Virt"),
                 Err(err) => {
@@ -64,6 +76,35 @@ impl Alibaba {
         }
     }
 
+    /// Retrieves a metadata token from the metadata server, as required by instances in hardened mode.
+    fn get_token(&self, client: &reqwest::blocking::Client, metadata_uri: &str) -> Option<String> {
+        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
+        tracing::trace!(
+            "Retrieving {} metadata token from: {}",
+            IDENTIFIER,
+            token_url
+        );
+
+        let token = match client.put(token_url).header(TOKEN_TTL_HEADER, "60").send() {
+            Ok(resp) if resp.status().is_success() => resp.text().ok()?,
+            Ok(resp) => {
+                tracing::trace!("Metadata token request rejected: {}", resp.status());
+                return None;
+            }
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                return None;
+            }
+        };
+
+        if token.is_empty() {
+            tracing::trace!("Metadata token is empty");
+            return None;
+        }
+
+        Some(token)
+    }
+
     /// Tries to identify Alibaba using vendor file(s).
     fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
         tracing::trace!(
@@ -134,6 +175,33 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_check_metadata_server_token() {
+        let mut server = Server::new();
+
+        let url = server.url();
+
+        let token_mock = server
+            .mock("PUT", METADATA_TOKEN_PATH)
+            .match_header(TOKEN_TTL_HEADER, "60")
+            .with_status(200)
+            .with_body("123abc")
+            .create();
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .match_header(TOKEN_HEADER, "123abc")
+            .with_status(200)
+            .with_body("ECS Virt")
+            .create();
+
+        let provider = Alibaba;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        token_mock.assert();
+        mock.assert();
+        assert!(result);
+    }
+
     #[test]
     fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
@@ -1,13 +1,17 @@
 //! Amazon Web Services (AWS).
+//!
+//! EC2 instances are identified by their vendor files or the instance metadata service (IMDS). ECS tasks, including
+//! those on Fargate, cannot reach the IMDS. Instead, they are identified by the task metadata endpoint that ECS
+//! advertises to every container through an environment variable.
 
+use std::env;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
@@ -15,6 +19,12 @@ const METADATA_PATH: &str = "/latest/dynamic/instance-identity/document";
 const METADATA_TOKEN_PATH: &str = "/latest/api/token";
 const PRODUCT_VERSION_FILE: &str = "/sys/class/dmi/id/product_version";
 const BIOS_VENDOR_FILE: &str = "/sys/class/dmi/id/bios_vendor";
+/// Environment variables holding the task metadata endpoint, for versions 4 and 3 of the endpoint.
+const TASK_METADATA_ENV_VARS: [&str; 2] = [
+    "ECS_CONTAINER_METADATA_URI_V4",
+    "ECS_CONTAINER_METADATA_URI",
+];
+const TASK_METADATA_PATH: &str = "/task";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::AWS;
 
 #[derive(Serialize, Deserialize)]
@@ -25,6 +35,12 @@ struct MetadataResponse {
     instance_id: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct TaskMetadataResponse {
+    #[serde(rename = "TaskARN")]
+    task_arn: String,
+}
+
 pub(crate) struct Aws;
 
 impl Provider for Aws {
@@ -37,6 +53,7 @@ impl Provider for Aws {
         tracing::trace!("Checking Amazon Web Services");
         if self.check_product_version_file(PRODUCT_VERSION_FILE)
             || self.check_bios_vendor_file(BIOS_VENDOR_FILE)
+            || self.check_task_metadata(&TASK_METADATA_ENV_VARS, timeout)
             || self.check_metadata_server_imdsv2(METADATA_URI, timeout)
             || self.check_metadata_server_imdsv1(METADATA_URI, timeout)
         {
@@ -46,6 +63,12 @@ impl Provider for Aws {
             }
         }
     }
+
+    /// Tries to identify Amazon Web Services without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_product_version_file(PRODUCT_VERSION_FILE)
+            || self.check_bios_vendor_file(BIOS_VENDOR_FILE)
+    }
 }
 
 impl Aws {
@@ -54,7 +77,7 @@ impl Aws {
         let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
         tracing::trace!("Retrieving {} IMDSv2 token from: {}", IDENTIFIER, token_url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
@@ -62,14 +85,18 @@ impl Aws {
         };
 
         let token = match client
-            .get(token_url)
+            .put(token_url)
             .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
             .send()
         {
-            Ok(resp) => resp.text().unwrap_or_else(|err| {
+            Ok(resp) if resp.status().is_success() => resp.text().unwrap_or_else(|err| {
                 tracing::trace!("Error reading token: {:?}", err);
                 String::new()
             }),
+            Ok(resp) => {
+                tracing::trace!("IMDSv2 token request rejected: {}", resp.status());
+                return false;
+            }
             Err(err) => {
                 tracing::trace!("Error making request: {:?}", err);
                 return false;
@@ -117,7 +144,7 @@ impl Aws {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
@@ -141,6 +168,40 @@ impl Aws {
         }
     }
 
+    /// Tries to identify an ECS task via the task metadata endpoint given by the first of `env_vars` that is set.
+    fn check_task_metadata(&self, env_vars: &[&str], timeout: Duration) -> bool {
+        let Some(metadata_uri) = env_vars
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+        else {
+            return false;
+        };
+        let url = format!("{}{TASK_METADATA_PATH}", metadata_uri.trim_end_matches('/'));
+        tracing::trace!("Checking {} task metadata using url: {}", IDENTIFIER, url);
+
+        let client = if let Ok(client) = providers::build_client(timeout) {
+            client
+        } else {
+            tracing::trace!("Error creating client");
+            return false;
+        };
+
+        match client.get(url).send() {
+            Ok(resp) => match resp.json::<TaskMetadataResponse>() {
+                // Also covers the `aws-cn` and `aws-us-gov` partitions
+                Ok(metadata) => metadata.task_arn.starts_with("arn:aws"),
+                Err(err) => {
+                    tracing::trace!("Error reading response: {:?}", err);
+                    false
+                }
+            },
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                false
+            }
+        }
+    }
+
     /// Tries to identify AWS using the product version file.
     fn check_product_version_file<P: AsRef<Path>>(&self, product_version_file: P) -> bool {
         tracing::trace!(
@@ -200,7 +261,7 @@ mod tests {
         let url = server.url();
 
         let token_mock = server
-            .mock("GET", METADATA_TOKEN_PATH)
+            .mock("PUT", METADATA_TOKEN_PATH)
             .with_status(200)
             .with_body("123abc")
             .create();
@@ -225,7 +286,7 @@ mod tests {
         let url = server.url();
 
         let token_mock = server
-            .mock("GET", METADATA_TOKEN_PATH)
+            .mock("PUT", METADATA_TOKEN_PATH)
             .with_status(200)
             .with_body("123abc")
             .create();
@@ -244,6 +305,27 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_check_metadata_server_imdsv2_token_disabled() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let token_mock = server
+            .mock("PUT", METADATA_TOKEN_PATH)
+            .with_status(403)
+            .with_body("Forbidden")
+            .create();
+
+        let metadata_mock = server.mock("GET", METADATA_PATH).expect(0).create();
+
+        let provider = Aws;
+        let result = provider.check_metadata_server_imdsv2(&url, Duration::from_secs(1));
+
+        token_mock.assert();
+        metadata_mock.assert();
+        assert!(!result);
+    }
+
     #[test]
     fn test_check_metadata_server_imdsv1_success() {
         let mut server = Server::new();
@@ -280,6 +362,45 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_check_task_metadata_success() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/v4/abc123/task")
+            .with_status(200)
+            .with_body(r#"{"TaskARN":"arn:aws:ecs:us-west-2:111122223333:task/default/abc123"}"#)
+            .create();
+
+        // Use a variable only this test touches, as tests share the process environment
+        env::set_var(
+            "CLOUD_DETECT_TEST_BLOCKING_ECS_CONTAINER_METADATA_URI_V4",
+            format!("{url}/v4/abc123"),
+        );
+
+        let provider = Aws;
+        let result = provider.check_task_metadata(
+            &["CLOUD_DETECT_TEST_BLOCKING_ECS_CONTAINER_METADATA_URI_V4"],
+            Duration::from_secs(1),
+        );
+
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_ECS_CONTAINER_METADATA_URI_V4");
+        mock.assert();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_task_metadata_unset() {
+        let provider = Aws;
+        let result = provider.check_task_metadata(
+            &["CLOUD_DETECT_TEST_BLOCKING_ECS_UNSET_METADATA_URI"],
+            Duration::from_secs(1),
+        );
+
+        assert!(!result);
+    }
+
     #[test]
     fn test_check_product_version_file_success() -> Result<()> {
         let mut product_version_file = NamedTempFile::new()?;
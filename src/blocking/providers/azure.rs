@@ -5,10 +5,9 @@ use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
@@ -45,6 +44,11 @@ impl Provider for Azure {
             }
         }
     }
+
+    /// Tries to identify Microsoft Azure without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE)
+    }
 }
 
 impl Azure {
@@ -53,7 +57,7 @@ impl Azure {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
@@ -126,6 +130,25 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_check_metadata_server_extra_fields() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"compute":{"vmId":"vm-123abc","location":"westeurope","osType":"Linux","tagsList":[{"name":"env","value":"prod"}],"securityProfile":{"secureBootEnabled":"true"}},"network":{"interface":[]},"extendedLocation":{"type":"edge"}}"#)
+            .create();
+
+        let provider = Azure;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(result);
+    }
+
     #[test]
     fn test_check_metadata_server_failure() {
         let mut server = Server::new();
@@ -1,19 +1,23 @@
 //! DigitalOcean.
+//!
+//! Droplets are identified by their vendor file or the droplet metadata server. App Platform containers have access to
+//! neither, so as a fallback they are identified by the `APP_*` environment variables App Platform sets for every
+//! component.
 
-use std::fs;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
+use std::{env, fs};
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/metadata/v1.json";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+const ENV_VARS: [&str; 2] = ["APP_ID", "APP_DOMAIN"];
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::DigitalOcean;
 
 pub(crate) struct DigitalOcean;
@@ -31,7 +35,9 @@ impl Provider for DigitalOcean {
     /// Tries to identify DigitalOcean using all the implemented options.
     fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
         tracing::trace!("Checking DigitalOcean");
-        if self.check_vendor_file(VENDOR_FILE) || self.check_metadata_server(METADATA_URI, timeout)
+        if self.check_vendor_file(VENDOR_FILE)
+            || self.check_metadata_server(METADATA_URI, timeout)
+            || self.check_environment(&ENV_VARS)
         {
             tracing::trace!("Identified DigitalOcean");
             if let Err(err) = tx.send(IDENTIFIER) {
@@ -39,6 +45,11 @@ impl Provider for DigitalOcean {
             }
         }
     }
+
+    /// Tries to identify DigitalOcean without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE) || self.check_environment(&ENV_VARS)
+    }
 }
 
 impl DigitalOcean {
@@ -47,7 +58,7 @@ impl DigitalOcean {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
@@ -89,6 +100,19 @@ impl DigitalOcean {
 
         false
     }
+
+    /// Tries to identify DigitalOcean App Platform using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .all(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
 }
 
 #[cfg(test)]
@@ -161,4 +185,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_BLOCKING_DO_APP_ID", "4f6c71e2");
+        env::set_var(
+            "CLOUD_DETECT_TEST_BLOCKING_DO_APP_DOMAIN",
+            "sample-app.ondigitalocean.app",
+        );
+
+        let provider = DigitalOcean;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_BLOCKING_DO_APP_ID",
+            "CLOUD_DETECT_TEST_BLOCKING_DO_APP_DOMAIN",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_DO_APP_ID");
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_DO_APP_DOMAIN");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = DigitalOcean;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_BLOCKING_DO_APP_ID_UNSET",
+            "CLOUD_DETECT_TEST_BLOCKING_DO_APP_DOMAIN_UNSET",
+        ]);
+
+        assert!(!result);
+    }
 }
@@ -0,0 +1,115 @@
+//! Equinix Metal.
+//!
+//! Unlike most providers, Equinix Metal serves its metadata over HTTPS from a DNS name rather than a link-local IP.
+
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blocking::{providers, Provider};
+use crate::ProviderId;
+
+const METADATA_URI: &str = "https://metadata.platformequinix.com";
+const METADATA_PATH: &str = "/metadata";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::EquinixMetal;
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    id: String,
+    plan: String,
+}
+
+pub(crate) struct EquinixMetal;
+
+impl Provider for EquinixMetal {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Equinix Metal using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
+        tracing::trace!("Checking Equinix Metal");
+        if self.check_metadata_server(METADATA_URI, timeout) {
+            tracing::trace!("Identified Equinix Metal");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+}
+
+impl EquinixMetal {
+    /// Tries to identify Equinix Metal via metadata server.
+    fn check_metadata_server(&self, metadata_uri: &str, timeout: Duration) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        let client = if let Ok(client) = providers::build_client(timeout) {
+            client
+        } else {
+            tracing::trace!("Error creating client");
+            return false;
+        };
+
+        let resp = match client.get(url).send() {
+            Ok(resp) => resp,
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                return false;
+            }
+        };
+
+        if !resp.status().is_success() {
+            tracing::trace!("Metadata request failed with status: {}", resp.status());
+            return false;
+        }
+
+        match resp.json::<MetadataResponse>() {
+            Ok(metadata) => !metadata.id.is_empty() && !metadata.plan.is_empty(),
+            Err(err) => {
+                tracing::trace!("Error reading response: {:?}", err);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+
+    use super::*;
+
+    #[test]
+    fn test_check_metadata_server_success() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body(r#"{"id": "7a2b5c1e-0d4f-4e3a-9b8c-1f2e3d4c5b6a", "plan": "c3.small.x86"}"#)
+            .create();
+
+        let provider = EquinixMetal;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_metadata_server_failure() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server.mock("GET", METADATA_PATH).with_status(404).create();
+
+        let provider = EquinixMetal;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(!result);
+    }
+}
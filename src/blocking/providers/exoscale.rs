@@ -0,0 +1,172 @@
+//! Exoscale.
+//!
+//! Exoscale serves its metadata at the same address and under the same prefix as AWS, so the metadata is only trusted
+//! if it reports one of Exoscale's zones.
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use crate::blocking::{providers, Provider};
+use crate::ProviderId;
+
+const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/latest/meta-data/availability-zone";
+const ZONES: [&str; 7] = [
+    "at-vie-1", "at-vie-2", "bg-sof-1", "ch-dk-2", "ch-gva-2", "de-fra-1", "de-muc-1",
+];
+const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Exoscale;
+
+pub(crate) struct Exoscale;
+
+impl Provider for Exoscale {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Exoscale using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
+        tracing::trace!("Checking Exoscale");
+        if self.check_vendor_file(PRODUCT_NAME_FILE)
+            || self.check_metadata_server(METADATA_URI, timeout)
+        {
+            tracing::trace!("Identified Exoscale");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify Exoscale without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(PRODUCT_NAME_FILE)
+    }
+}
+
+impl Exoscale {
+    /// Tries to identify Exoscale via metadata server.
+    fn check_metadata_server(&self, metadata_uri: &str, timeout: Duration) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        let client = if let Ok(client) = providers::build_client(timeout) {
+            client
+        } else {
+            tracing::trace!("Error creating client");
+            return false;
+        };
+
+        match client.get(url).send() {
+            Ok(resp) if resp.status().is_success() => match resp.text() {
+                Ok(zone) => ZONES.contains(&zone.trim()),
+                Err(err) => {
+                    tracing::trace!("Error reading response: {:?}", err);
+                    false
+                }
+            },
+            Ok(resp) => {
+                tracing::trace!("Unexpected response status: {}", resp.status());
+                false
+            }
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                false
+            }
+        }
+    }
+
+    /// Tries to identify Exoscale via vendor file.
+    fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        tracing::trace!(
+            "Checking {} vendor file using path: {:?}",
+            IDENTIFIER,
+            vendor_file.as_ref().display()
+        );
+
+        if vendor_file.as_ref().is_file() {
+            return match fs::read_to_string(vendor_file) {
+                Ok(content) => content.contains("Exoscale"),
+                Err(err) => {
+                    tracing::trace!("Error reading file: {:?}", err);
+                    false
+                }
+            };
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use mockito::Server;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_check_metadata_server_success() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body("de-fra-1")
+            .create();
+
+        let provider = Exoscale;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_metadata_server_failure() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(404)
+            .with_body("Not Found")
+            .create();
+
+        let provider = Exoscale;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Exoscale Compute Platform")?;
+
+        let provider = Exoscale;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Exoscale;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//! Fly.io.
+//!
+//! Fly Machines run in Firecracker microVMs, which expose neither a recognizable vendor file nor a standard metadata
+//! server. Instead, Fly.io sets `FLY_*` environment variables in every Machine.
+
+use std::env;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use crate::blocking::Provider;
+use crate::ProviderId;
+
+const ENV_VARS: [&str; 2] = ["FLY_ALLOC_ID", "FLY_APP_NAME"];
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::FlyIo;
+
+pub(crate) struct FlyIo;
+
+impl Provider for FlyIo {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Fly.io using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, _timeout: Duration) {
+        tracing::trace!("Checking Fly.io");
+        if self.check_environment(&ENV_VARS) {
+            tracing::trace!("Identified Fly.io");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify Fly.io without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_environment(&ENV_VARS)
+    }
+}
+
+impl FlyIo {
+    /// Tries to identify Fly.io using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .any(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_BLOCKING_FLY_ALLOC_ID", "abc123");
+
+        let provider = FlyIo;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_BLOCKING_FLY_ALLOC_ID",
+            "CLOUD_DETECT_TEST_BLOCKING_FLY_APP_NAME",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_FLY_ALLOC_ID");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = FlyIo;
+        let result = provider.check_environment(&["CLOUD_DETECT_TEST_BLOCKING_FLY_UNSET"]);
+
+        assert!(!result);
+    }
+}
@@ -1,18 +1,21 @@
 //! Google Cloud Platform (GCP).
+//!
+//! Compute Engine instances are identified by their vendor file or the metadata server. Serverless platforms (Cloud
+//! Run, App Engine and Cloud Functions) are identified by the environment variables they set, such as `K_SERVICE`
+//! (Cloud Run) and `GAE_ENV` (App Engine).
 
-use std::fs;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
+use std::{env, fs};
 
-use reqwest::blocking::Client;
-
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://metadata.google.internal";
 const METADATA_PATH: &str = "/computeMetadata/v1/instance/tags";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/product_name";
+const ENV_VARS: [&str; 2] = ["K_SERVICE", "GAE_ENV"];
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::GCP;
 
 pub struct Gcp;
@@ -25,7 +28,9 @@ impl Provider for Gcp {
     /// Tries to identify GCP using all the implemented options.
     fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
         tracing::trace!("Checking Google Cloud Platform");
-        if self.check_vendor_file(VENDOR_FILE) || self.check_metadata_server(METADATA_URI, timeout)
+        if self.check_vendor_file(VENDOR_FILE)
+            || self.check_metadata_server(METADATA_URI, timeout)
+            || self.check_environment(&ENV_VARS)
         {
             tracing::trace!("Identified Google Cloud Platform");
             if let Err(err) = tx.send(IDENTIFIER) {
@@ -33,6 +38,11 @@ impl Provider for Gcp {
             }
         }
     }
+
+    /// Tries to identify Google Cloud Platform without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE) || self.check_environment(&ENV_VARS)
+    }
 }
 
 impl Gcp {
@@ -41,7 +51,7 @@ impl Gcp {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
@@ -80,6 +90,19 @@ impl Gcp {
 
         false
     }
+
+    /// Tries to identify GCP serverless platforms using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .any(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
 }
 
 #[cfg(test)]
@@ -144,4 +167,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use a variable only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_BLOCKING_GAE_ENV", "standard");
+
+        let provider = Gcp;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_BLOCKING_K_SERVICE",
+            "CLOUD_DETECT_TEST_BLOCKING_GAE_ENV",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_GAE_ENV");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = Gcp;
+        let result = provider.check_environment(&["CLOUD_DETECT_TEST_BLOCKING_K_SERVICE_UNSET"]);
+
+        assert!(!result);
+    }
 }
@@ -0,0 +1,243 @@
+//! IBM Cloud.
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blocking::{providers, Provider};
+use crate::ProviderId;
+
+const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/metadata/v1/instance";
+const METADATA_TOKEN_PATH: &str = "/instance_identity/v1/token";
+const METADATA_VERSION: &str = "2022-03-01";
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::IBM;
+
+#[derive(Serialize, Deserialize)]
+struct TokenRequest {
+    expires_in: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    id: String,
+    crn: String,
+}
+
+pub(crate) struct Ibm;
+
+impl Provider for Ibm {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify IBM Cloud using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
+        tracing::trace!("Checking IBM Cloud");
+        if self.check_vendor_file(VENDOR_FILE) || self.check_metadata_server(METADATA_URI, timeout)
+        {
+            tracing::trace!("Identified IBM Cloud");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify IBM Cloud without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE)
+    }
+}
+
+impl Ibm {
+    /// Tries to identify IBM Cloud via metadata server.
+    fn check_metadata_server(&self, metadata_uri: &str, timeout: Duration) -> bool {
+        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}?version={METADATA_VERSION}");
+        tracing::trace!("Retrieving {} token from: {}", IDENTIFIER, token_url);
+
+        let client = if let Ok(client) = providers::build_client(timeout) {
+            client
+        } else {
+            tracing::trace!("Error creating client");
+            return false;
+        };
+
+        let token = match client
+            .put(token_url)
+            .header("Metadata-Flavor", "ibm")
+            .json(&TokenRequest { expires_in: 300 })
+            .send()
+        {
+            Ok(resp) => match resp.json::<TokenResponse>() {
+                Ok(resp) => resp.access_token,
+                Err(err) => {
+                    tracing::trace!("Error reading token: {:?}", err);
+                    return false;
+                }
+            },
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                return false;
+            }
+        };
+
+        if token.is_empty() {
+            tracing::trace!("Token is empty");
+            return false;
+        }
+
+        // Request to use the token to get metadata
+        let metadata_url = format!("{metadata_uri}{METADATA_PATH}?version={METADATA_VERSION}");
+        tracing::trace!(
+            "Checking {} metadata using url: {}",
+            IDENTIFIER,
+            metadata_url
+        );
+
+        let resp = match client.get(metadata_url).bearer_auth(token).send() {
+            Ok(resp) => resp.json::<MetadataResponse>(),
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                return false;
+            }
+        };
+
+        match resp {
+            Ok(metadata) => !metadata.id.is_empty() && metadata.crn.starts_with("crn:v1:bluemix:"),
+            Err(err) => {
+                tracing::trace!("Error reading response: {:?}", err);
+                false
+            }
+        }
+    }
+
+    /// Tries to identify IBM Cloud using vendor file(s).
+    fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        tracing::trace!(
+            "Checking {} vendor file: {}",
+            IDENTIFIER,
+            vendor_file.as_ref().display()
+        );
+
+        if vendor_file.as_ref().is_file() {
+            return match fs::read_to_string(vendor_file) {
+                Ok(content) => content.contains("IBM"),
+                Err(err) => {
+                    tracing::trace!("Error reading file: {:?}", err);
+                    false
+                }
+            };
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use mockito::{Matcher, Server};
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_check_metadata_server_success() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let token_mock = server
+            .mock("PUT", METADATA_TOKEN_PATH)
+            .match_query(Matcher::UrlEncoded(
+                "version".into(),
+                METADATA_VERSION.into(),
+            ))
+            .match_header("Metadata-Flavor", "ibm")
+            .with_status(200)
+            .with_body(r#"{"access_token": "123abc"}"#)
+            .create();
+
+        let metadata_mock = server
+            .mock("GET", METADATA_PATH)
+            .match_query(Matcher::UrlEncoded(
+                "version".into(),
+                METADATA_VERSION.into(),
+            ))
+            .match_header("Authorization", "Bearer 123abc")
+            .with_status(200)
+            .with_body(
+                r#"{"id": "0717_abc123", "crn": "crn:v1:bluemix:public:is:us-south-1:a/123::instance:0717_abc123"}"#,
+            )
+            .create();
+
+        let provider = Ibm;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        token_mock.assert();
+        metadata_mock.assert();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_metadata_server_failure() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let token_mock = server
+            .mock("PUT", METADATA_TOKEN_PATH)
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"access_token": "123abc"}"#)
+            .create();
+
+        let metadata_mock = server
+            .mock("GET", METADATA_PATH)
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"id": "abc", "crn": "abc"}"#)
+            .create();
+
+        let provider = Ibm;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        token_mock.assert();
+        metadata_mock.assert();
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"IBM")?;
+
+        let provider = Ibm;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Ibm;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
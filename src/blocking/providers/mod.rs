@@ -1,6 +1,12 @@
 //! Provider modules.
 
-#[cfg(feature = "akami")]
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+
+use crate::MAX_REDIRECTS;
+
+#[cfg(feature = "akamai")]
 pub(crate) mod akamai;
 #[cfg(feature = "alibaba")]
 pub(crate) mod alibaba;
@@ -8,13 +14,43 @@ pub(crate) mod alibaba;
 pub(crate) mod aws;
 #[cfg(feature = "azure")]
 pub(crate) mod azure;
+#[cfg(feature = "civo")]
+pub(crate) mod civo;
 #[cfg(feature = "digitalocean")]
 pub(crate) mod digitalocean;
+#[cfg(feature = "equinix")]
+pub(crate) mod equinix;
+#[cfg(feature = "exoscale")]
+pub(crate) mod exoscale;
+#[cfg(feature = "fly")]
+pub(crate) mod fly;
 #[cfg(feature = "gcp")]
 pub(crate) mod gcp;
+#[cfg(feature = "ibm")]
+pub(crate) mod ibm;
+#[cfg(feature = "nutanix")]
+pub(crate) mod nutanix;
 #[cfg(feature = "oci")]
 pub(crate) mod oci;
 #[cfg(feature = "openstack")]
 pub(crate) mod openstack;
+#[cfg(feature = "ovh")]
+pub(crate) mod ovh;
+#[cfg(feature = "render")]
+pub(crate) mod render;
+#[cfg(feature = "upcloud")]
+pub(crate) mod upcloud;
+#[cfg(feature = "vmware")]
+pub(crate) mod vmware;
 #[cfg(feature = "vultr")]
 pub(crate) mod vultr;
+
+/// Builds an HTTP client for metadata requests with the given timeout.
+// Unused if only providers without a metadata server (e.g. Fly.io) are enabled
+#[allow(dead_code)]
+pub(crate) fn build_client(timeout: Duration) -> reqwest::Result<Client> {
+    Client::builder()
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .build()
+}
@@ -0,0 +1,97 @@
+//! Nutanix.
+//!
+//! Nutanix AHV runs on-premises without a metadata server at a well-known address, so only vendor files are checked.
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use crate::blocking::Provider;
+use crate::ProviderId;
+
+const SYS_VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Nutanix;
+
+pub(crate) struct Nutanix;
+
+impl Provider for Nutanix {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Nutanix using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, _timeout: Duration) {
+        tracing::trace!("Checking Nutanix");
+        if self.identify_offline() {
+            tracing::trace!("Identified Nutanix");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify Nutanix without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(SYS_VENDOR_FILE) || self.check_vendor_file(PRODUCT_NAME_FILE)
+    }
+}
+
+impl Nutanix {
+    /// Tries to identify Nutanix via vendor file.
+    fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        tracing::trace!(
+            "Checking {} vendor file using path: {:?}",
+            IDENTIFIER,
+            vendor_file.as_ref().display()
+        );
+
+        if vendor_file.as_ref().is_file() {
+            return match fs::read_to_string(vendor_file) {
+                Ok(content) => content.contains("Nutanix"),
+                Err(err) => {
+                    tracing::trace!("Error reading file: {:?}", err);
+                    false
+                }
+            };
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Nutanix")?;
+
+        let provider = Nutanix;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Nutanix;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
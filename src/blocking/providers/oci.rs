@@ -5,10 +5,9 @@ use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
@@ -40,6 +39,11 @@ impl Provider for Oci {
             }
         }
     }
+
+    /// Tries to identify Oracle Cloud Infrastructure without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE)
+    }
 }
 
 impl Oci {
@@ -48,7 +52,7 @@ impl Oci {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
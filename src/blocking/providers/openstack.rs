@@ -1,19 +1,34 @@
 //! OpenStack.
+//!
+//! Clouds built on OpenStack that have their own provider, such as OVHcloud, also match the checks here. When the
+//! more specific provider is enabled and matches, OpenStack defers to it rather than reporting a generic result.
 
 use std::fs;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
-
-use crate::blocking::Provider;
+use crate::blocking::{providers, Provider};
 use crate::ProviderId;
 
 const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/openstack/";
+/// Metadata versions listed by the metadata server root.
+const METADATA_VERSIONS: [&str; 10] = [
+    "latest",
+    "2020-10-14",
+    "2018-08-27",
+    "2017-02-22",
+    "2016-10-06",
+    "2016-06-30",
+    "2015-10-15",
+    "2013-10-17",
+    "2013-04-04",
+    "2012-08-10",
+];
 const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
-const PRODUCT_NAMES: [&str; 2] = ["Openstack Nova", "OpenStack Compute"];
+// Nova writes "OpenStack Nova", though some older deployments capitalised it as "Openstack Nova".
+const PRODUCT_NAMES: [&str; 3] = ["OpenStack Nova", "Openstack Nova", "OpenStack Compute"];
 const CHASSIS_ASSET_TAG_FILE: &str = "/sys/class/dmi/id/chassis_asset_tag";
 const CHASSIS_ASSET_TAGS: [&str; 5] = [
     "HUAWEICLOUD",
@@ -37,12 +52,33 @@ impl Provider for OpenStack {
         if self.check_vendor_files(PRODUCT_NAME_FILE, CHASSIS_ASSET_TAG_FILE)
             || self.check_metadata_server(METADATA_URI, timeout)
         {
+            #[cfg(feature = "ovh")]
+            if crate::blocking::providers::ovh::Ovh.is_ovh(timeout) {
+                tracing::trace!("Deferring OpenStack to OVHcloud");
+                return;
+            }
+
             tracing::trace!("Identified OpenStack");
             if let Err(err) = tx.send(IDENTIFIER) {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    /// Tries to identify OpenStack without making any network requests.
+    fn identify_offline(&self) -> bool {
+        if !self.check_vendor_files(PRODUCT_NAME_FILE, CHASSIS_ASSET_TAG_FILE) {
+            return false;
+        }
+
+        #[cfg(feature = "ovh")]
+        if crate::blocking::providers::ovh::Ovh.identify_offline() {
+            tracing::trace!("Deferring OpenStack to OVHcloud");
+            return false;
+        }
+
+        true
+    }
 }
 
 impl OpenStack {
@@ -51,15 +87,28 @@ impl OpenStack {
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = Client::builder().timeout(timeout).build() {
+        let client = if let Ok(client) = providers::build_client(timeout) {
             client
         } else {
             tracing::trace!("Error creating client");
             return false;
         };
 
-        match client.get(url).send() {
-            Ok(resp) => resp.status().is_success(),
+        // Other providers' metadata servers share the address, so the response must list a known metadata version
+        match client
+            .get(url)
+            .send()
+            .and_then(|resp| resp.error_for_status())
+        {
+            Ok(resp) => match resp.text() {
+                Ok(text) => text
+                    .lines()
+                    .any(|line| METADATA_VERSIONS.contains(&line.trim())),
+                Err(err) => {
+                    tracing::trace!("Error reading response: {:?}", err);
+                    false
+                }
+            },
             Err(err) => {
                 tracing::trace!("Error making request: {:?}", err);
                 false
@@ -130,7 +179,11 @@ mod tests {
         let mut server = Server::new();
         let url = server.url();
 
-        let mock = server.mock("GET", METADATA_PATH).with_status(200).create();
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body("2012-08-10\n2013-04-04\nlatest\n")
+            .create();
 
         let provider = OpenStack;
         let result = provider.check_metadata_server(&url, Duration::from_secs(1));
@@ -139,6 +192,24 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_check_metadata_server_other_provider() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body(r#"{"imageId":"ami-123abc","instanceId":"i-123abc"}"#)
+            .create();
+
+        let provider = OpenStack;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(!result);
+    }
+
     #[test]
     fn test_check_metadata_server_failure() {
         let mut server = Server::new();
@@ -183,4 +254,22 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_vendor_files_product_name() -> Result<()> {
+        for product_name in PRODUCT_NAMES {
+            let mut product_name_file = NamedTempFile::new()?;
+            let chassis_asset_tag_file = NamedTempFile::new()?;
+
+            product_name_file.write_all(product_name.as_bytes())?;
+
+            let provider = OpenStack;
+            let result = provider
+                .check_vendor_files(product_name_file.path(), chassis_asset_tag_file.path());
+
+            assert!(result, "{product_name}");
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,192 @@
+//! OVHcloud.
+//!
+//! OVHcloud Public Cloud is built on OpenStack, so its instances also match the OpenStack provider. The OpenStack
+//! provider defers to this one whenever the OVHcloud-specific markers below are present.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blocking::{providers, Provider};
+use crate::ProviderId;
+
+const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/openstack/latest/meta_data.json";
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+const MARKER: &str = "ovh";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::OVH;
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    #[serde(default)]
+    availability_zone: String,
+    #[serde(default)]
+    meta: HashMap<String, String>,
+}
+
+impl MetadataResponse {
+    fn is_ovh(&self) -> bool {
+        let is_marker = |value: &str| value.to_lowercase().contains(MARKER);
+
+        is_marker(&self.availability_zone)
+            || self
+                .meta
+                .iter()
+                .any(|(key, value)| is_marker(key) || is_marker(value))
+    }
+}
+
+pub(crate) struct Ovh;
+
+impl Provider for Ovh {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify OVHcloud using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, timeout: Duration) {
+        tracing::trace!("Checking OVHcloud");
+        if self.is_ovh(timeout) {
+            tracing::trace!("Identified OVHcloud");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify OVHcloud without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_vendor_file(VENDOR_FILE)
+    }
+}
+
+impl Ovh {
+    /// Checks whether the host is an OVHcloud instance, for other providers that need to defer to it.
+    pub(crate) fn is_ovh(&self, timeout: Duration) -> bool {
+        self.check_vendor_file(VENDOR_FILE) || self.check_metadata_server(METADATA_URI, timeout)
+    }
+
+    /// Tries to identify OVHcloud via metadata server.
+    fn check_metadata_server(&self, metadata_uri: &str, timeout: Duration) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        let client = if let Ok(client) = providers::build_client(timeout) {
+            client
+        } else {
+            tracing::trace!("Error creating client");
+            return false;
+        };
+
+        match client.get(url).send() {
+            Ok(resp) => match resp.json::<MetadataResponse>() {
+                Ok(metadata) => metadata.is_ovh(),
+                Err(err) => {
+                    tracing::trace!("Error reading response: {:?}", err);
+                    false
+                }
+            },
+            Err(err) => {
+                tracing::trace!("Error making request: {:?}", err);
+                false
+            }
+        }
+    }
+
+    /// Tries to identify OVHcloud using vendor file(s).
+    fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        tracing::trace!(
+            "Checking {} vendor file: {}",
+            IDENTIFIER,
+            vendor_file.as_ref().display()
+        );
+
+        if vendor_file.as_ref().is_file() {
+            return match fs::read_to_string(vendor_file) {
+                Ok(content) => content.contains("OVH"),
+                Err(err) => {
+                    tracing::trace!("Error reading file: {:?}", err);
+                    false
+                }
+            };
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use mockito::Server;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_check_metadata_server_success() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body(r#"{"availability_zone": "nova", "meta": {"ovh-region": "GRA11"}}"#)
+            .create();
+
+        let provider = Ovh;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_metadata_server_failure() {
+        let mut server = Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", METADATA_PATH)
+            .with_status(200)
+            .with_body(r#"{"availability_zone": "nova", "meta": {}}"#)
+            .create();
+
+        let provider = Ovh;
+        let result = provider.check_metadata_server(&url, Duration::from_secs(1));
+
+        mock.assert();
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"OVH SAS")?;
+
+        let provider = Ovh;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Ovh;
+        let result = provider.check_vendor_file(vendor_file.path());
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
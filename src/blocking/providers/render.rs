@@ -0,0 +1,85 @@
+//! Render.
+//!
+//! Render services run in a managed environment without a recognizable vendor file or a metadata server. Instead,
+//! Render sets `RENDER` and `RENDER_*` environment variables for every service.
+
+use std::env;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use crate::blocking::Provider;
+use crate::ProviderId;
+
+const ENV_VARS: [&str; 2] = ["RENDER", "RENDER_SERVICE_ID"];
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Render;
+
+pub(crate) struct Render;
+
+impl Provider for Render {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Render using all the implemented options.
+    fn identify(&self, tx: SyncSender<ProviderId>, _timeout: Duration) {
+        tracing::trace!("Checking Render");
+        if self.check_environment(&ENV_VARS) {
+            tracing::trace!("Identified Render");
+            if let Err(err) = tx.send(IDENTIFIER) {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Tries to identify Render without making any network requests.
+    fn identify_offline(&self) -> bool {
+        self.check_environment(&ENV_VARS)
+    }
+}
+
+impl Render {
+    /// Tries to identify Render using environment variable(s).
+    ///
+    /// `RENDER` alone is too generic a name to rely on, so every variable must be set.
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .all(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_BLOCKING_RENDER", "true");
+        env::set_var("CLOUD_DETECT_TEST_BLOCKING_RENDER_SERVICE_ID", "srv-abc123");
+
+        let provider = Render;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_BLOCKING_RENDER",
+            "CLOUD_DETECT_TEST_BLOCKING_RENDER_SERVICE_ID",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_RENDER");
+        env::remove_var("CLOUD_DETECT_TEST_BLOCKING_RENDER_SERVICE_ID");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = Render;
+        let result = provider.check_environment(&["CLOUD_DETECT_TEST_BLOCKING_RENDER_UNSET"]);
+
+        assert!(!result);
+    }
+}
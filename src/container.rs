@@ -0,0 +1,151 @@
+//! Container runtime detection.
+//!
+//! System container runtimes (e.g. LXC and systemd-nspawn) name themselves in the `container` environment variable of
+//! the container's init process, which systemd also records in `/run/systemd/container`. Docker and Podman set
+//! neither, but leave marker files in the container's filesystem instead.
+
+use std::path::Path;
+
+use tokio::fs;
+
+use crate::ContainerRuntime;
+
+const SYSTEMD_CONTAINER_FILE: &str = "run/systemd/container";
+const INIT_ENVIRON_FILE: &str = "proc/1/environ";
+const DOCKER_ENV_FILE: &str = ".dockerenv";
+const PODMAN_ENV_FILE: &str = "run/.containerenv";
+
+/// Identifies the container runtime the host runs in, if any.
+pub(crate) async fn detect() -> Option<ContainerRuntime> {
+    detect_in(Path::new("/")).await
+}
+
+/// Identifies the container runtime from the files under the given root directory.
+pub(crate) async fn detect_in(root: &Path) -> Option<ContainerRuntime> {
+    if let Ok(name) = fs::read_to_string(root.join(SYSTEMD_CONTAINER_FILE)).await {
+        let name = name.trim();
+        if !name.is_empty() {
+            return Some(from_name(name));
+        }
+    }
+
+    // Reading the init process's environment usually requires root
+    if let Some(name) = fs::read(root.join(INIT_ENVIRON_FILE))
+        .await
+        .ok()
+        .and_then(|environ| container_var(&environ))
+    {
+        return Some(from_name(&name));
+    }
+
+    if fs::try_exists(root.join(DOCKER_ENV_FILE))
+        .await
+        .unwrap_or(false)
+    {
+        return Some(ContainerRuntime::Docker);
+    }
+
+    if fs::try_exists(root.join(PODMAN_ENV_FILE))
+        .await
+        .unwrap_or(false)
+    {
+        return Some(ContainerRuntime::Podman);
+    }
+
+    None
+}
+
+/// Extracts the `container` variable from the given NUL-separated `/proc/<pid>/environ` contents.
+fn container_var(environ: &[u8]) -> Option<String> {
+    environ
+        .split(|byte| *byte == 0)
+        .find_map(|var| var.strip_prefix(b"container="))
+        .map(|value| String::from_utf8_lossy(value).trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Identifies the container runtime from the name it gives itself in the `container` variable.
+fn from_name(name: &str) -> ContainerRuntime {
+    match name {
+        "lxc" | "lxc-libvirt" => ContainerRuntime::Lxc,
+        "systemd-nspawn" => ContainerRuntime::SystemdNspawn,
+        "docker" => ContainerRuntime::Docker,
+        "podman" => ContainerRuntime::Podman,
+        _ => ContainerRuntime::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Creates a root directory holding the given files.
+    fn fixture(files: &[(&str, &[u8])]) -> Result<TempDir> {
+        let root = tempfile::tempdir()?;
+        for (path, content) in files {
+            let path = root.path().join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+
+        Ok(root)
+    }
+
+    #[tokio::test]
+    async fn test_detect_in_lxc() -> Result<()> {
+        let root = fixture(&[(
+            INIT_ENVIRON_FILE,
+            b"PATH=/usr/sbin:/usr/bin\0container=lxc\0TERM=linux\0",
+        )])?;
+
+        assert_eq!(detect_in(root.path()).await, Some(ContainerRuntime::Lxc));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_detect_in_nspawn() -> Result<()> {
+        let root = fixture(&[
+            (SYSTEMD_CONTAINER_FILE, b"systemd-nspawn\n"),
+            // The systemd file takes precedence
+            (INIT_ENVIRON_FILE, b"container=lxc\0"),
+        ])?;
+
+        assert_eq!(
+            detect_in(root.path()).await,
+            Some(ContainerRuntime::SystemdNspawn)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_detect_in_docker() -> Result<()> {
+        let root = fixture(&[(DOCKER_ENV_FILE, b""), (INIT_ENVIRON_FILE, b"HOME=/root\0")])?;
+
+        assert_eq!(detect_in(root.path()).await, Some(ContainerRuntime::Docker));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_detect_in_none() -> Result<()> {
+        let root = fixture(&[(INIT_ENVIRON_FILE, b"HOME=/root\0TERM=linux\0")])?;
+
+        assert_eq!(detect_in(root.path()).await, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(from_name("lxc-libvirt"), ContainerRuntime::Lxc);
+        assert_eq!(from_name("podman"), ContainerRuntime::Podman);
+        assert_eq!(from_name("wsl"), ContainerRuntime::Other);
+    }
+}
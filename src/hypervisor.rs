@@ -0,0 +1,178 @@
+//! Hypervisor detection.
+//!
+//! The hypervisor is identified from the same DMI vendor files as the providers, falling back to the Xen hypervisor
+//! type and the CPU's `hypervisor` flag, which is set on any virtualized host.
+
+use crate::providers::common;
+use crate::{Hypervisor, ProviderId};
+
+const SYS_VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
+const HYPERVISOR_TYPE_FILE: &str = "/sys/hypervisor/type";
+const CPUINFO_FILE: &str = "/proc/cpuinfo";
+
+/// System vendors that only make physical hardware, as reported by their DMI vendor files.
+const PHYSICAL_VENDORS: &[&str] = &[
+    "Dell Inc.",
+    "LENOVO",
+    "ASUSTeK",
+    "HP",
+    "Hewlett-Packard",
+    "Supermicro",
+    "Gigabyte",
+    "Micro-Star",
+];
+
+/// Identifies the hypervisor the host runs on, if any.
+pub(crate) async fn detect() -> Option<Hypervisor> {
+    let sys_vendor = read(SYS_VENDOR_FILE).await;
+    let product_name = read(PRODUCT_NAME_FILE).await;
+    if let Some(hypervisor) = from_dmi(&sys_vendor, &product_name) {
+        return Some(hypervisor);
+    }
+
+    if read(HYPERVISOR_TYPE_FILE).await.trim() == "xen" {
+        return Some(Hypervisor::Xen);
+    }
+
+    has_hypervisor_flag(&read(CPUINFO_FILE).await).then_some(Hypervisor::Other)
+}
+
+/// Whether the host is clearly physical hardware, made by a known hardware vendor and not running under a hypervisor.
+pub(crate) async fn is_bare_metal() -> bool {
+    is_bare_metal_at(SYS_VENDOR_FILE, CPUINFO_FILE).await
+}
+
+/// Whether the given system vendor and CPU info files mark the host as physical hardware.
+pub(crate) async fn is_bare_metal_at(sys_vendor_file: &str, cpuinfo_file: &str) -> bool {
+    is_physical_vendor(&read(sys_vendor_file).await)
+        && !has_hypervisor_flag(&read(cpuinfo_file).await)
+}
+
+/// Whether the given DMI system vendor only makes physical hardware.
+fn is_physical_vendor(sys_vendor: &str) -> bool {
+    let sys_vendor = sys_vendor.trim();
+    PHYSICAL_VENDORS
+        .iter()
+        .any(|vendor| sys_vendor.starts_with(vendor))
+}
+
+/// Reads the given file, treating a missing or unreadable file as empty.
+async fn read(file: &str) -> String {
+    common::read_vendor_file(ProviderId::Unknown, file)
+        .await
+        .unwrap_or_default()
+}
+
+/// Identifies the hypervisor from the DMI system vendor and product name.
+fn from_dmi(sys_vendor: &str, product_name: &str) -> Option<Hypervisor> {
+    // KVM guests usually report QEMU as their vendor, so the product name is checked first
+    if product_name.contains("KVM") || sys_vendor.contains("KVM") {
+        Some(Hypervisor::Kvm)
+    } else if sys_vendor.contains("QEMU") {
+        Some(Hypervisor::Qemu)
+    } else if sys_vendor.contains("Xen") || product_name.contains("HVM domU") {
+        Some(Hypervisor::Xen)
+    } else if sys_vendor.contains("VMware") {
+        Some(Hypervisor::VMware)
+    } else if sys_vendor.contains("Microsoft Corporation")
+        && product_name.contains("Virtual Machine")
+    {
+        Some(Hypervisor::HyperV)
+    } else {
+        None
+    }
+}
+
+/// Whether the CPU flags in the given `/proc/cpuinfo` contents mark the host as virtualized.
+fn has_hypervisor_flag(cpuinfo: &str) -> bool {
+    cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn test_from_dmi() {
+        let cases = [
+            (
+                "QEMU",
+                "Standard PC (i440FX + PIIX, 1996)",
+                Some(Hypervisor::Qemu),
+            ),
+            ("Red Hat", "KVM", Some(Hypervisor::Kvm)),
+            ("QEMU", "KVM Virtual Machine", Some(Hypervisor::Kvm)),
+            ("Xen", "HVM domU", Some(Hypervisor::Xen)),
+            (
+                "VMware, Inc.",
+                "VMware Virtual Platform",
+                Some(Hypervisor::VMware),
+            ),
+            (
+                "Microsoft Corporation",
+                "Virtual Machine",
+                Some(Hypervisor::HyperV),
+            ),
+            // Microsoft also makes physical hardware, such as the Surface
+            ("Microsoft Corporation", "Surface Pro", None),
+            ("Dell Inc.", "PowerEdge R640", None),
+            ("", "", None),
+        ];
+
+        for (sys_vendor, product_name, expected) in cases {
+            assert_eq!(
+                from_dmi(sys_vendor, product_name),
+                expected,
+                "{sys_vendor} / {product_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_has_hypervisor_flag() {
+        assert!(has_hypervisor_flag(
+            "processor\t: 0\nflags\t\t: fpu vme de pse tsc msr hypervisor lahf_lm\n"
+        ));
+        assert!(!has_hypervisor_flag(
+            "processor\t: 0\nflags\t\t: fpu vme de pse tsc msr lahf_lm\n"
+        ));
+        assert!(!has_hypervisor_flag(""));
+    }
+
+    #[test]
+    fn test_is_physical_vendor() {
+        assert!(is_physical_vendor("Dell Inc.\n"));
+        assert!(is_physical_vendor("LENOVO"));
+        assert!(is_physical_vendor("ASUSTeK COMPUTER INC."));
+        assert!(!is_physical_vendor("QEMU"));
+        assert!(!is_physical_vendor("Amazon EC2"));
+        assert!(!is_physical_vendor(""));
+    }
+
+    #[tokio::test]
+    async fn test_is_bare_metal_at() -> Result<()> {
+        let mut sys_vendor_file = NamedTempFile::new()?;
+        sys_vendor_file.write_all(b"Dell Inc.\n")?;
+        let mut cpuinfo_file = NamedTempFile::new()?;
+        cpuinfo_file.write_all(b"processor\t: 0\nflags\t\t: fpu vme de pse tsc msr lahf_lm\n")?;
+        let mut virtual_cpuinfo_file = NamedTempFile::new()?;
+        virtual_cpuinfo_file
+            .write_all(b"processor\t: 0\nflags\t\t: fpu vme hypervisor lahf_lm\n")?;
+
+        let sys_vendor = sys_vendor_file.path().to_str().unwrap();
+        assert!(is_bare_metal_at(sys_vendor, cpuinfo_file.path().to_str().unwrap()).await);
+        assert!(!is_bare_metal_at(sys_vendor, virtual_cpuinfo_file.path().to_str().unwrap()).await);
+        assert!(!is_bare_metal_at("/nonexistent", cpuinfo_file.path().to_str().unwrap()).await);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//! Command-line tool printing the host's cloud service provider.
+//!
+//! Run with `cloud-detect --help` for the available options.
+
+use std::time::Duration;
+
+use clap::Parser;
+use cloud_detect::ProviderId;
+
+/// Detect the cloud service provider of the host.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Maximum time allowed for detection, in seconds.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+
+    /// Only check local vendor files, without making any network requests.
+    #[arg(long, conflicts_with = "all")]
+    offline: bool,
+
+    /// Print every provider that matches the host, rather than only the first.
+    #[arg(long)]
+    all: bool,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+    let timeout = args.timeout;
+
+    if args.all {
+        let providers = cloud_detect::detect_all(timeout).await;
+        print_all(&providers, args.json);
+        return;
+    }
+
+    let provider = if args.offline {
+        cloud_detect::detect_offline(timeout).await
+    } else {
+        cloud_detect::detect_optional(timeout)
+            .await
+            .unwrap_or_default()
+    };
+    print_one(&provider, args.json);
+}
+
+/// Parses a timeout given in (possibly fractional) seconds, such as `0.5`.
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|err| format!("{err}"))?;
+
+    Duration::try_from_secs_f64(seconds).map_err(|err| format!("{err}"))
+}
+
+/// Prints the detected provider, e.g. `aws` or `{"provider":"aws"}`.
+fn print_one(provider: &ProviderId, json: bool) {
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "provider": provider.to_string() })
+        );
+    } else {
+        println!("{provider}");
+    }
+}
+
+/// Prints every matching provider, one per line or as `{"providers":["aws","kubernetes"]}`.
+fn print_all(providers: &[ProviderId], json: bool) {
+    if json {
+        let providers: Vec<String> = providers.iter().map(ToString::to_string).collect();
+        println!("{}", serde_json::json!({ "providers": providers }));
+    } else {
+        for provider in providers {
+            println!("{provider}");
+        }
+    }
+}
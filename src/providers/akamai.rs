@@ -1,12 +1,16 @@
 //! Akamai Cloud
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/v1/instance";
 const METADATA_TOKEN_PATH: &str = "/v1/token";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::Akamai;
@@ -15,6 +19,8 @@ pub(crate) const IDENTIFIER: ProviderId = ProviderId::Akamai;
 struct MetadataResponse {
     id: isize,
     host_uuid: String,
+    #[serde(default)]
+    region: Option<String>,
 }
 
 pub(crate) struct Akamai;
@@ -26,87 +32,96 @@ impl Provider for Akamai {
     }
 
     /// Tries to identify Akamai using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Akamai Cloud");
-        if self.check_metadata_server(METADATA_URI).await {
-            tracing::trace!("Identified Akamai Cloud");
-            let res = tx.send(IDENTIFIER).await;
+        let method = if ctx.metadata_server
+            && self
+                .check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI))
+                .await
+        {
+            Some(DetectionMethod::MetadataServer)
+        } else {
+            None
+        };
+
+        if let Some(method) = method {
+            tracing::trace!("Identified Akamai Cloud using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_TOKEN_PATH, METADATA_PATH])
+    }
+
+    /// Retrieves the region from the instance metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region
+    }
+
+    /// Retrieves the Linode ID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.id.to_string())
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl Akamai {
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
+    /// Tries to identify Akamai via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .is_some_and(|metadata| metadata.id > 0 && !metadata.host_uuid.is_empty())
+    }
+
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
         let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
         tracing::trace!("Retrieving {} token from: {}", IDENTIFIER, token_url);
 
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-
-        let token = match client
-            .get(token_url)
-            .header("Metadata-Token-Expiry-Seconds", "60")
-            .send()
-            .await
-        {
-            Ok(resp) => resp.text().await.unwrap_or_else(|err| {
-                tracing::trace!("Error reading token: {:?}", err);
-                String::new()
-            }),
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                return false;
-            }
-        };
+        let token_req = common::get(ctx, &token_url, &[("Metadata-Token-Expiry-Seconds", "60")]);
+        let token = common::send_text(ctx, IDENTIFIER, token_req).await?;
 
         if token.is_empty() {
             tracing::trace!("Token is empty");
-            return false;
+            return None;
         }
 
         // Request to use token to get metadata
         let metadata_url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!(
-            "Checking {} metadata using url: {}",
-            IDENTIFIER,
-            metadata_url,
-        );
 
-        let resp = match client
-            .get(metadata_url)
-            .header("Metadata-Token", token)
-            .send()
-            .await
-        {
-            Ok(resp) => resp.json::<MetadataResponse>().await,
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                return false;
-            }
-        };
-
-        match resp {
-            Ok(metadata) => metadata.id > 0 && !metadata.host_uuid.is_empty(),
-            Err(err) => {
-                tracing::trace!("Error reading response: {:?}", err);
-                false
-            }
-        }
+        common::get_metadata(
+            ctx,
+            IDENTIFIER,
+            &metadata_url,
+            &[("Metadata-Token", &token)],
+        )
+        .await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use wiremock::matchers::{header, path};
+    use anyhow::Result;
+    use reqwest::Client;
+    use tokio::sync::mpsc;
+    use wiremock::matchers::{any, header, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
@@ -126,14 +141,16 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 id: 123,
                 host_uuid: "123456".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Akamai;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -152,15 +169,40 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 id: 0,
                 host_uuid: "".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Akamai;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
+
+    #[tokio::test]
+    async fn test_identify_offline() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        // Route all requests through the mock server, so that any request made is recorded
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let provider = Akamai;
+        provider.identify(&Context::offline(client), tx).await;
+
+        assert!(rx.recv().await.is_none());
+
+        Ok(())
+    }
 }
@@ -1,16 +1,27 @@
 //! Alibaba Cloud.
+//!
+//! Instances in hardened mode reject metadata requests without a token, which is obtained from the metadata server
+//! much like an AWS IMDSv2 token. A token is always requested first, and requests are only made without one if the
+//! metadata server does not hand one out.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
-use tokio::fs;
+use reqwest::Url;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId, DEFAULT_DETECTION_TIMEOUT};
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://100.100.100.200";
-const METADATA_PATH: &str = "/latest/meta-data/latest/meta-data/instance/virtualization-solution";
+pub(crate) const METADATA_URI: &str = "http://100.100.100.200";
+const METADATA_PATH: &str = "/latest/meta-data/instance/virtualization-solution";
+const METADATA_ID_PATH: &str = "/latest/meta-data/instance-id";
+const METADATA_TOKEN_PATH: &str = "/latest/api/token";
+const TOKEN_HEADER: &str = "X-aliyun-ecs-metadata-token";
+const TOKEN_TTL_HEADER: &str = "X-aliyun-ecs-metadata-token-ttl-seconds";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const PRODUCT_NAME: &str = "Alibaba Cloud ECS";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::Alibaba;
 
 pub(crate) struct Alibaba;
@@ -22,71 +33,101 @@ impl Provider for Alibaba {
     }
 
     /// Tries to identify Alibaba Cloud using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Alibaba Cloud");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified Alibaba Cloud");
-            let res = tx.send(IDENTIFIER).await;
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Alibaba Cloud using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(
+            METADATA_URI,
+            &[METADATA_TOKEN_PATH, METADATA_PATH, METADATA_ID_PATH],
+        )
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the instance ID from the metadata server.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_text(ctx, ctx.metadata_uri(METADATA_URI), METADATA_ID_PATH)
+            .await
+            .map(|text| text.trim().to_string())
+    }
+
+    /// Retrieves the raw virtualization metadata, which is served as plain text.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(Value::String)
+    }
 }
 
 impl Alibaba {
     /// Tries to identify Alibaba via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let url = format!("{metadata_uri}{METADATA_PATH}");
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata(ctx, metadata_uri)
+            .await
+            .is_some_and(|text| text.contains("ECS Virt"))
+    }
+
+    /// Retrieves the virtualization solution of the instance from the metadata server.
+    async fn get_metadata(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        self.get_text(ctx, metadata_uri, METADATA_PATH).await
+    }
+
+    /// Retrieves the plain text metadata at the given path, using a metadata token if one can be retrieved.
+    async fn get_text(&self, ctx: &Context, metadata_uri: &str, path: &str) -> Option<String> {
+        let token = self.get_token(ctx, metadata_uri).await;
+        let headers: Vec<(&str, &str)> = token
+            .iter()
+            .map(|token| (TOKEN_HEADER, token.as_str()))
+            .collect();
+
+        let url = format!("{metadata_uri}{path}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = reqwest::Client::builder()
-            .timeout(DEFAULT_DETECTION_TIMEOUT)
-            .build()
-        {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-
-        match client.get(url).send().await {
-            Ok(resp) => match resp.text().await {
-                Ok(text) => text.contains("ECS Virt"),
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &headers)).await
     }
 
-    /// Tries to identify Alibaba using vendor file(s).
-    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+    /// Retrieves a metadata token from the metadata server, as required by instances in hardened mode.
+    async fn get_token(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
         tracing::trace!(
-            "Checking {} vendor file: {}",
+            "Retrieving {} metadata token from: {}",
             IDENTIFIER,
-            vendor_file.as_ref().display()
+            token_url
         );
 
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("Alibaba Cloud ECS"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
+        let token_req = ctx.client.put(token_url).header(TOKEN_TTL_HEADER, "60");
+        let token = common::send_text(ctx, IDENTIFIER, token_req).await?;
+
+        if token.is_empty() {
+            tracing::trace!("Metadata token is empty");
+            return None;
         }
 
-        false
+        Some(token)
+    }
+
+    /// Tries to identify Alibaba using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, PRODUCT_NAME).await
     }
 }
 
@@ -95,8 +136,9 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
-    use wiremock::matchers::path;
+    use wiremock::matchers::{header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
@@ -111,8 +153,9 @@ mod tests {
             .await;
 
         let provider = Alibaba;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -127,12 +170,78 @@ mod tests {
             .await;
 
         let provider = Alibaba;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_check_metadata_server_token() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .and(header(TOKEN_TTL_HEADER, "60"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // In hardened mode, requests without the token are rejected
+        Mock::given(path(METADATA_PATH))
+            .and(header(TOKEN_HEADER, "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ECS Virt"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let provider = Alibaba;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[test]
+    fn test_metadata_endpoints() {
+        let endpoints: Vec<String> = Alibaba
+            .metadata_endpoints()
+            .iter()
+            .map(Url::to_string)
+            .collect();
+
+        assert!(endpoints.contains(
+            &"http://100.100.100.200/latest/meta-data/instance/virtualization-solution".to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_raw_metadata() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ECS Virt"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+
+        let provider = Alibaba;
+        let result = provider.raw_metadata(&Context::new(client)).await;
+
+        assert_eq!(result, Some(Value::String("ECS Virt".to_string())));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
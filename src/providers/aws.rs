@@ -1,19 +1,42 @@
 //! Amazon Web Services (AWS).
+//!
+//! EC2 instances are identified by their vendor files or the instance metadata service (IMDS). ECS tasks, including
+//! those on Fargate, cannot reach the IMDS. Instead, they are identified by the task metadata endpoint that ECS
+//! advertises to every container through an environment variable.
+//!
+//! The partition (`aws`, `aws-cn` or `aws-us-gov`) is reported as the environment, as China and GovCloud regions use
+//! their own service endpoints and ARNs.
 
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{AwsCredentials, Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/latest/dynamic/instance-identity/document";
 const METADATA_TOKEN_PATH: &str = "/latest/api/token";
+const METADATA_CREDENTIALS_PATH: &str = "/latest/meta-data/iam/security-credentials/";
+const METADATA_PARTITION_PATH: &str = "/latest/meta-data/services/partition";
+const METADATA_ZONE_PATH: &str = "/latest/meta-data/placement/availability-zone";
+const METADATA_LIFE_CYCLE_PATH: &str = "/latest/meta-data/instance-life-cycle";
 const PRODUCT_VERSION_FILE: &str = "/sys/class/dmi/id/product_version";
 const BIOS_VENDOR_FILE: &str = "/sys/class/dmi/id/bios_vendor";
+/// Marker found (in any case) in the vendor files of EC2 instances, e.g. `Amazon EC2` or `4.11.amazon`.
+pub(crate) const VENDOR_MARKER: &str = "amazon";
+/// Environment variables holding the task metadata endpoint, for versions 4 and 3 of the endpoint.
+const TASK_METADATA_ENV_VARS: [&str; 2] = [
+    "ECS_CONTAINER_METADATA_URI_V4",
+    "ECS_CONTAINER_METADATA_URI",
+];
+const TASK_METADATA_PATH: &str = "/task";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::AWS;
 
 #[derive(Serialize, Deserialize)]
@@ -22,6 +45,27 @@ struct MetadataResponse {
     image_id: String,
     #[serde(rename = "instanceId")]
     instance_id: String,
+    #[serde(default)]
+    region: Option<String>,
+}
+
+impl MetadataResponse {
+    fn is_aws(&self) -> bool {
+        self.image_id.starts_with("ami-") && self.instance_id.starts_with("i-")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskMetadataResponse {
+    #[serde(rename = "TaskARN")]
+    task_arn: String,
+}
+
+impl TaskMetadataResponse {
+    fn is_aws(&self) -> bool {
+        // Also covers the `aws-cn` and `aws-us-gov` partitions
+        self.task_arn.starts_with("arn:aws")
+    }
 }
 
 pub(crate) struct Aws;
@@ -33,158 +77,269 @@ impl Provider for Aws {
     }
 
     /// Tries to identify AWS using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Amazon Web Services");
-        if self.check_product_version_file(PRODUCT_VERSION_FILE).await
-            || self.check_bios_vendor_file(BIOS_VENDOR_FILE).await
-            || self.check_metadata_server_imdsv2(METADATA_URI).await
-            || self.check_metadata_server_imdsv1(METADATA_URI).await
-        {
-            tracing::trace!("Identified Amazon Web Services");
-            let res = tx.send(IDENTIFIER).await;
+        let vendor_file = async {
+            self.check_product_version_file(ctx.vendor_path(PRODUCT_VERSION_FILE))
+                .await
+                || self
+                    .check_bios_vendor_file(ctx.vendor_path(BIOS_VENDOR_FILE))
+                    .await
+        };
+        let metadata_server = async {
+            self.check_task_metadata(ctx, &TASK_METADATA_ENV_VARS).await
+                || self
+                    .check_metadata_server_imdsv2(ctx, ctx.metadata_uri(METADATA_URI))
+                    .await
+                || self
+                    .check_metadata_server_imdsv1(ctx, ctx.metadata_uri(METADATA_URI))
+                    .await
+        };
+        let methods =
+            common::check_vendor_file_and_metadata(ctx, vendor_file, metadata_server).await;
+
+        for method in methods {
+            tracing::trace!("Identified Amazon Web Services using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_TOKEN_PATH, METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[PRODUCT_VERSION_FILE, BIOS_VENDOR_FILE])
+    }
+
+    /// Retrieves the region from the instance identity document.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region
+    }
+
+    /// Retrieves the availability zone (e.g. `us-east-1a`) from the metadata server.
+    async fn availability_zone(&self, ctx: &Context) -> Option<String> {
+        self.get_text(ctx, ctx.metadata_uri(METADATA_URI), METADATA_ZONE_PATH)
+            .await
+    }
+
+    /// Retrieves the partition (e.g. `aws-cn`) from the metadata server, or derives it from the region if the metadata
+    /// server does not report one.
+    async fn environment(&self, ctx: &Context) -> Option<String> {
+        let metadata_uri = ctx.metadata_uri(METADATA_URI);
+
+        match self.get_partition(ctx, metadata_uri).await {
+            Some(partition) => Some(partition),
+            None => self
+                .get_metadata::<MetadataResponse>(ctx, metadata_uri)
+                .await?
+                .region
+                .map(|region| partition_for_region(&region).to_string()),
+        }
+    }
+
+    /// Retrieves the instance ID from the instance identity document.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.instance_id)
+    }
+
+    /// Retrieves whether the instance is a spot instance from its life cycle (`spot`, `on-demand` or `scheduled`).
+    async fn is_interruptible(&self, ctx: &Context) -> Option<bool> {
+        self.get_text(
+            ctx,
+            ctx.metadata_uri(METADATA_URI),
+            METADATA_LIFE_CYCLE_PATH,
+        )
+        .await
+        .map(|life_cycle| life_cycle == "spot")
+    }
+
+    /// Retrieves the raw instance identity document.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl Aws {
     /// Tries to identify AWS via metadata server (using IMDSv2).
-    async fn check_metadata_server_imdsv2(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
-        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
-        tracing::trace!("Retrieving {} IMDSv2 token from: {}", IDENTIFIER, token_url);
+    async fn check_metadata_server_imdsv2(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata_imdsv2::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .is_some_and(|metadata| metadata.is_aws())
+    }
+
+    /// Tries to identify AWS via metadata server (using IMDSv1).
+    async fn check_metadata_server_imdsv1(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata_imdsv1::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .is_some_and(|metadata| metadata.is_aws())
+    }
 
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
+    /// Tries to identify an ECS task via the task metadata endpoint given by the first of `env_vars` that is set.
+    async fn check_task_metadata(&self, ctx: &Context, env_vars: &[&str]) -> bool {
+        let Some(metadata_uri) = env_vars
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+        else {
             return false;
         };
+        let url = format!("{}{TASK_METADATA_PATH}", metadata_uri.trim_end_matches('/'));
 
-        let token = match client
-            .put(token_url)
-            .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
-            .send()
-            .await
-        {
-            Ok(resp) => resp.text().await.unwrap_or_else(|err| {
-                tracing::trace!("Error reading token: {:?}", err);
-                String::new()
-            }),
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                return false;
-            }
-        };
+        common::check_metadata(
+            ctx,
+            IDENTIFIER,
+            &url,
+            &[],
+            |metadata: TaskMetadataResponse| metadata.is_aws(),
+        )
+        .await
+    }
 
-        if token.is_empty() {
-            tracing::trace!("IMDSv2 token is empty");
-            return false;
+    /// Retrieves the instance identity document from the metadata server, falling back to IMDSv1 if IMDSv2 fails.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        match self.get_metadata_imdsv2(ctx, metadata_uri).await {
+            Some(metadata) => Some(metadata),
+            None => self.get_metadata_imdsv1(ctx, metadata_uri).await,
         }
+    }
 
-        // Request to use the token to get metadata
-        let metadata_url = format!("{metadata_uri}{METADATA_PATH}");
+    /// Retrieves the security credentials of the instance profile role from the metadata server.
+    ///
+    /// The role is not known up front, so it is first discovered by listing the roles attached to the instance (of
+    /// which there is at most one). Both requests use an IMDSv2 token when one can be retrieved, and IMDSv1 otherwise.
+    pub(crate) async fn get_instance_credentials(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<AwsCredentials> {
+        let token = self.get_token(ctx, metadata_uri).await;
+        let headers: Vec<(&str, &str)> = token
+            .iter()
+            .map(|token| ("X-aws-ec2-metadata-token", token.as_str()))
+            .collect();
+
+        let roles_url = format!("{metadata_uri}{METADATA_CREDENTIALS_PATH}");
         tracing::trace!(
-            "Checking {} metadata using url: {}",
+            "Retrieving {} instance profile role from: {}",
             IDENTIFIER,
-            metadata_url
+            roles_url
         );
 
-        let resp = match client
-            .get(metadata_url)
-            .header("X-aws-ec2-metadata-token", token)
-            .send()
-            .await
-        {
-            Ok(resp) => resp.json::<MetadataResponse>().await,
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                return false;
-            }
+        let roles =
+            common::send_text(ctx, IDENTIFIER, common::get(ctx, &roles_url, &headers)).await?;
+        let Some(role) = roles.lines().map(str::trim).find(|role| !role.is_empty()) else {
+            tracing::trace!("No instance profile role is attached");
+            return None;
         };
 
-        match resp {
-            Ok(metadata) => {
-                metadata.image_id.starts_with("ami-") && metadata.instance_id.starts_with("i-")
-            }
-            Err(err) => {
-                tracing::trace!("Error reading response: {:?}", err);
-                false
-            }
-        }
+        let url = format!("{roles_url}{role}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &headers).await
     }
 
-    /// Tries to identify AWS via metadata server (using IMDSv1).
-    async fn check_metadata_server_imdsv1(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
-        let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+    /// Retrieves the partition of the instance from the metadata server.
+    async fn get_partition(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        self.get_text(ctx, metadata_uri, METADATA_PARTITION_PATH)
+            .await
+    }
 
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
+    /// Retrieves a plain-text metadata value from the metadata server, using an IMDSv2 token if one can be retrieved.
+    async fn get_text(&self, ctx: &Context, metadata_uri: &str, path: &str) -> Option<String> {
+        let token = self.get_token(ctx, metadata_uri).await;
+        let headers: Vec<(&str, &str)> = token
+            .iter()
+            .map(|token| ("X-aws-ec2-metadata-token", token.as_str()))
+            .collect();
 
-        match client.get(url).send().await {
-            Ok(resp) => match resp.json::<MetadataResponse>().await {
-                Ok(resp) => resp.image_id.starts_with("ami-") && resp.instance_id.starts_with("i-"),
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
+        let url = format!("{metadata_uri}{path}");
+        tracing::trace!("Retrieving {} metadata from: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &headers))
+            .await
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
     }
 
-    /// Tries to identify AWS using the product version file.
-    async fn check_product_version_file<P: AsRef<Path>>(&self, product_version_file: P) -> bool {
-        tracing::trace!(
-            "Checking {} product version file: {}",
-            IDENTIFIER,
-            product_version_file.as_ref().display()
-        );
+    /// Retrieves a session token for IMDSv2 from the metadata server.
+    async fn get_token(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}");
+        tracing::trace!("Retrieving {} IMDSv2 token from: {}", IDENTIFIER, token_url);
 
-        if product_version_file.as_ref().is_file() {
-            return match fs::read_to_string(product_version_file).await {
-                Ok(content) => content.to_lowercase().contains("amazon"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
+        let token_req = ctx
+            .client
+            .put(token_url)
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "60");
+        let token = common::send_text(ctx, IDENTIFIER, token_req).await?;
+
+        if token.is_empty() {
+            tracing::trace!("IMDSv2 token is empty");
+            return None;
         }
 
-        false
+        Some(token)
+    }
+
+    /// Retrieves the instance identity document from the metadata server (using IMDSv2).
+    async fn get_metadata_imdsv2<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let token = self.get_token(ctx, metadata_uri).await?;
+
+        // Request to use the token to get metadata
+        let metadata_url = format!("{metadata_uri}{METADATA_PATH}");
+        let headers = [("X-aws-ec2-metadata-token", token.as_str())];
+
+        common::get_metadata(ctx, IDENTIFIER, &metadata_url, &headers).await
+    }
+
+    /// Retrieves the instance identity document from the metadata server (using IMDSv1).
+    async fn get_metadata_imdsv1<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
+
+    /// Tries to identify AWS using the product version file.
+    async fn check_product_version_file<P: AsRef<Path>>(&self, product_version_file: P) -> bool {
+        common::read_vendor_file(IDENTIFIER, product_version_file)
+            .await
+            .is_some_and(|content| content.to_lowercase().contains(VENDOR_MARKER))
     }
 
     /// Tries to identify AWS using the BIOS vendor file.
     async fn check_bios_vendor_file<P: AsRef<Path>>(&self, bios_vendor_file: P) -> bool {
-        tracing::trace!(
-            "Checking {} BIOS vendor file: {}",
-            IDENTIFIER,
-            bios_vendor_file.as_ref().display()
-        );
-
-        if bios_vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(bios_vendor_file).await {
-                Ok(content) => content.to_lowercase().contains("amazon"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
+        common::read_vendor_file(IDENTIFIER, bios_vendor_file)
+            .await
+            .is_some_and(|content| content.to_lowercase().contains(VENDOR_MARKER))
+    }
+}
 
-        false
+/// Returns the partition that the given region (e.g. `cn-north-1`) belongs to.
+fn partition_for_region(region: &str) -> &'static str {
+    if region.starts_with("cn-") {
+        "aws-cn"
+    } else if region.starts_with("us-gov-") {
+        "aws-us-gov"
+    } else {
+        "aws"
     }
 }
 
@@ -193,8 +348,9 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
-    use wiremock::matchers::{header, path};
+    use wiremock::matchers::{header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
@@ -214,14 +370,18 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 image_id: "ami-123abc".to_string(),
                 instance_id: "i-123abc".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Aws;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server_imdsv2(&metadata_uri).await;
+        let result = provider
+            .check_metadata_server_imdsv2(&ctx, &metadata_uri)
+            .await;
 
         assert!(result);
     }
@@ -240,14 +400,18 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 image_id: "abc".to_string(),
                 instance_id: "abc".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Aws;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server_imdsv2(&metadata_uri).await;
+        let result = provider
+            .check_metadata_server_imdsv2(&ctx, &metadata_uri)
+            .await;
 
         assert!(!result);
     }
@@ -259,14 +423,18 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 image_id: "ami-123abc".to_string(),
                 instance_id: "i-123abc".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Aws;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server_imdsv1(&metadata_uri).await;
+        let result = provider
+            .check_metadata_server_imdsv1(&ctx, &metadata_uri)
+            .await;
 
         assert!(result);
     }
@@ -278,16 +446,498 @@ mod tests {
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 image_id: "abc".to_string(),
                 instance_id: "abc".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Aws;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server_imdsv1(&metadata_uri).await;
+        let result = provider
+            .check_metadata_server_imdsv1(&ctx, &metadata_uri)
+            .await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_exoscale() {
+        // Exoscale serves metadata under the same prefix, but no instance identity document
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/latest/meta-data/availability-zone"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ch-gva-2"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/latest/meta-data/instance-id"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("8ae3bd78-8dd2-4eb2-9c8e-7bd3ab7b1f98"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+
+        assert!(
+            !provider
+                .check_metadata_server_imdsv2(&ctx, &metadata_uri)
+                .await
+        );
+        assert!(
+            !provider
+                .check_metadata_server_imdsv1(&ctx, &metadata_uri)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_token_required() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                image_id: "ami-123abc".to_string(),
+                instance_id: "i-123abc".to_string(),
+                region: None,
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Requests without a token are rejected when IMDSv2 is enforced
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+
+        assert!(
+            provider
+                .check_metadata_server_imdsv2(&ctx, &metadata_uri)
+                .await
+        );
+        assert!(
+            !provider
+                .check_metadata_server_imdsv1(&ctx, &metadata_uri)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_token_disabled() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Forbidden"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                image_id: "ami-123abc".to_string(),
+                instance_id: "i-123abc".to_string(),
+                region: None,
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+
+        assert!(
+            !provider
+                .check_metadata_server_imdsv2(&ctx, &metadata_uri)
+                .await
+        );
+        assert!(
+            provider
+                .check_metadata_server_imdsv1(&ctx, &metadata_uri)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_imdsv2_region() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                image_id: "ami-123abc".to_string(),
+                instance_id: "i-123abc".to_string(),
+                region: Some("us-east-1".to_string()),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata_imdsv2::<MetadataResponse>(&ctx, &metadata_uri)
+            .await;
+
+        assert_eq!(
+            result.and_then(|metadata| metadata.region),
+            Some("us-east-1".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_imdsv1_fallback() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                image_id: "ami-123abc".to_string(),
+                instance_id: "i-123abc".to_string(),
+                region: None,
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await;
+
+        assert_eq!(
+            result.map(|metadata| metadata.instance_id),
+            Some("i-123abc".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_environment_partition() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PARTITION_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("aws-us-gov"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let result = provider.get_partition(&ctx, &mock_server.uri()).await;
+
+        assert_eq!(result.as_deref(), Some("aws-us-gov"));
+    }
+
+    #[tokio::test]
+    async fn test_availability_zone() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_ZONE_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("us-east-1a"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.availability_zone(&ctx).await;
+
+        assert_eq!(result.as_deref(), Some("us-east-1a"));
+    }
+
+    #[tokio::test]
+    async fn test_is_interruptible_spot() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_LIFE_CYCLE_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("spot"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.is_interruptible(&ctx).await;
+
+        assert_eq!(result, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_is_interruptible_on_demand() {
+        let mock_server = MockServer::start().await;
+
+        // Without a token, IMDSv1 is used instead
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_LIFE_CYCLE_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("on-demand"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.is_interruptible(&ctx).await;
+
+        assert_eq!(result, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_environment_from_region() -> Result<()> {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .mount(&mock_server)
+            .await;
+
+        // Without a partition in the metadata, it is derived from the region instead
+        Mock::given(path(METADATA_PARTITION_PATH))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                image_id: "ami-123abc".to_string(),
+                instance_id: "i-123abc".to_string(),
+                region: Some("cn-north-1".to_string()),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+
+        let provider = Aws;
+        let result = provider.environment(&Context::new(client)).await;
+
+        assert_eq!(result.as_deref(), Some("aws-cn"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_partition_for_region() {
+        assert_eq!(partition_for_region("cn-north-1"), "aws-cn");
+        assert_eq!(partition_for_region("cn-northwest-1"), "aws-cn");
+        assert_eq!(partition_for_region("us-gov-west-1"), "aws-us-gov");
+        assert_eq!(partition_for_region("us-east-1"), "aws");
+        assert_eq!(partition_for_region("eu-central-1"), "aws");
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_credentials() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_CREDENTIALS_PATH))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("web-server-role\n"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(format!("{METADATA_CREDENTIALS_PATH}web-server-role")))
+            .and(header("X-aws-ec2-metadata-token", "123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "Code": "Success",
+                    "LastUpdated": "2024-05-01T12:00:00Z",
+                    "Type": "AWS-HMAC",
+                    "AccessKeyId": "ASIAEXAMPLE",
+                    "SecretAccessKey": "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+                    "Token": "IQoJb3JpZ2luX2VjEXAMPLE",
+                    "Expiration": "2024-05-01T18:00:00Z"
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let credentials = provider
+            .get_instance_credentials(&ctx, &metadata_uri)
+            .await
+            .expect("credentials");
+
+        assert_eq!(credentials.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(
+            credentials.secret_access_key,
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"
+        );
+        assert_eq!(credentials.token, "IQoJb3JpZ2luX2VjEXAMPLE");
+        assert_eq!(credentials.expiration, "2024-05-01T18:00:00Z");
+        assert!(!format!("{credentials:?}").contains("wJalrXUtnFEMI"));
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_credentials_no_role() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(path(METADATA_CREDENTIALS_PATH))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let credentials = provider.get_instance_credentials(&ctx, &metadata_uri).await;
+
+        assert!(credentials.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_task_metadata_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/v4/abc123/task"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(TaskMetadataResponse {
+                    task_arn: "arn:aws:ecs:us-west-2:111122223333:task/default/abc123".to_string(),
+                }),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var(
+            "CLOUD_DETECT_TEST_ECS_CONTAINER_METADATA_URI_V4",
+            format!("{}/v4/abc123", mock_server.uri()),
+        );
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let result = provider
+            .check_task_metadata(
+                &ctx,
+                &[
+                    "CLOUD_DETECT_TEST_ECS_CONTAINER_METADATA_URI_V4",
+                    "CLOUD_DETECT_TEST_ECS_CONTAINER_METADATA_URI",
+                ],
+            )
+            .await;
+
+        env::remove_var("CLOUD_DETECT_TEST_ECS_CONTAINER_METADATA_URI_V4");
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_task_metadata_failure() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/v3/task"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(TaskMetadataResponse {
+                    task_arn: "".to_string(),
+                }),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        env::set_var(
+            "CLOUD_DETECT_TEST_ECS_FAILURE_METADATA_URI",
+            format!("{}/v3", mock_server.uri()),
+        );
+
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let result = provider
+            .check_task_metadata(&ctx, &["CLOUD_DETECT_TEST_ECS_FAILURE_METADATA_URI"])
+            .await;
+
+        env::remove_var("CLOUD_DETECT_TEST_ECS_FAILURE_METADATA_URI");
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_task_metadata_unset() {
+        let provider = Aws;
+        let ctx = Context::new(Client::new());
+        let result = provider
+            .check_task_metadata(&ctx, &["CLOUD_DETECT_TEST_ECS_UNSET_METADATA_URI"])
+            .await;
 
         assert!(!result);
+        assert!(ctx.errors().is_empty());
     }
 
     #[tokio::test]
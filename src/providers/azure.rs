@@ -1,25 +1,54 @@
 //! Microsoft Azure.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
-const METADATA_PATH: &str = "/metadata/instance?api-version=2017-12-01";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+// `azEnvironment` was added in 2018-10-01.
+const METADATA_PATH: &str = "/metadata/instance?api-version=2021-02-01";
+const SCHEDULED_EVENTS_PATH: &str = "/metadata/scheduledevents?api-version=2020-07-01";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "Microsoft Corporation";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::Azure;
 
 #[derive(Serialize, Deserialize)]
 struct Compute {
     #[serde(rename = "vmId")]
     vm_id: String,
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(rename = "vmScaleSetName", default)]
+    vm_scale_set_name: Option<String>,
+    #[serde(rename = "azEnvironment", default)]
+    az_environment: Option<String>,
+    #[serde(default)]
+    zone: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct ScheduledEvents {
+    #[serde(rename = "Events", default)]
+    events: Vec<ScheduledEvent>,
+}
+
+#[derive(Deserialize)]
+struct ScheduledEvent {
+    #[serde(rename = "EventType")]
+    event_type: String,
+}
+
+// Fields not listed here are ignored, so detection keeps working as Azure adds to the metadata schema.
 #[derive(Serialize, Deserialize)]
 struct MetadataResponse {
     compute: Compute,
@@ -34,70 +63,144 @@ impl Provider for Azure {
     }
 
     /// Tries to identify Azure using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Microsoft Azure");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified Microsoft Azure");
-            let res = tx.send(IDENTIFIER).await;
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Microsoft Azure using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region from the compute metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .compute
+            .location
+    }
+
+    /// Retrieves the availability zone (e.g. `1`) from the compute metadata.
+    ///
+    /// This is empty for VMs not deployed to a zone, which is reported as `None`.
+    async fn availability_zone(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .compute
+            .zone
+            .filter(|zone| !zone.is_empty())
+    }
+
+    /// Retrieves the Azure cloud (e.g. `AzurePublicCloud` or `AzureUSGovernmentCloud`) from the compute metadata.
+    ///
+    /// Sovereign clouds such as `AzureChinaCloud` use different service endpoints from the public cloud.
+    async fn environment(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .compute
+            .az_environment
+            .filter(|environment| !environment.is_empty())
+    }
+
+    /// Retrieves the virtual machine scale set name from the compute metadata.
+    ///
+    /// This is empty for VMs outside a scale set, which is reported as `None`.
+    async fn scale_set(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .compute
+            .vm_scale_set_name
+            .filter(|scale_set| !scale_set.is_empty())
+    }
+
+    /// Retrieves the VM ID from the compute metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.compute.vm_id)
+    }
+
+    /// Retrieves whether the VM is a Spot VM from its priority (`Spot`, `Low` or `Regular`) in the compute metadata.
+    ///
+    /// If the priority is not reported, a pending `Preempt` event in the scheduled events, which are only raised for
+    /// Spot VMs about to be evicted, also marks the VM as interruptible.
+    async fn is_interruptible(&self, ctx: &Context) -> Option<bool> {
+        let metadata_uri = ctx.metadata_uri(METADATA_URI);
+        let priority = self
+            .get_metadata::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .and_then(|metadata| metadata.compute.priority)
+            .filter(|priority| !priority.is_empty());
+
+        match priority {
+            Some(priority) => Some(matches!(priority.as_str(), "Spot" | "Low")),
+            None => {
+                let url = format!("{metadata_uri}{SCHEDULED_EVENTS_PATH}");
+                let events: ScheduledEvents =
+                    common::get_metadata(ctx, IDENTIFIER, &url, &[("Metadata", "true")]).await?;
+
+                events
+                    .events
+                    .iter()
+                    .any(|event| event.event_type == "Preempt")
+                    .then_some(true)
+            }
+        }
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl Azure {
     /// Tries to identify Azure via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
         let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
-
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-        let req = client.get(url).header("Metadata", "true");
-
-        match req.send().await {
-            Ok(resp) => match resp.json::<MetadataResponse>().await {
-                Ok(resp) => !resp.compute.vm_id.is_empty(),
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
-    }
 
-    /// Tries to identify Azure using vendor file(s).
-    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
-        tracing::trace!(
-            "Checking {} vendor file: {}",
+        common::check_metadata(
+            ctx,
             IDENTIFIER,
-            vendor_file.as_ref().display()
-        );
+            &url,
+            &[("Metadata", "true")],
+            |metadata: MetadataResponse| !metadata.compute.vm_id.is_empty(),
+        )
+        .await
+    }
 
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("Microsoft Corporation"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[("Metadata", "true")]).await
+    }
 
-        false
+    /// Tries to identify Azure using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
     }
 }
 
@@ -106,6 +209,7 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
     use wiremock::matchers::query_param;
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -115,10 +219,15 @@ mod tests {
     #[tokio::test]
     async fn test_check_metadata_server_success() {
         let mock_server = MockServer::start().await;
-        Mock::given(query_param("api-version", "2017-12-01"))
+        Mock::given(query_param("api-version", "2021-02-01"))
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 compute: Compute {
                     vm_id: "vm-123abc".to_string(),
+                    location: None,
+                    vm_scale_set_name: None,
+                    az_environment: None,
+                    zone: None,
+                    priority: None,
                 },
             }))
             .expect(1)
@@ -126,8 +235,29 @@ mod tests {
             .await;
 
         let provider = Azure;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_extra_fields() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(r#"{"compute":{"vmId":"vm-123abc","location":"westeurope","osType":"Linux","tagsList":[{"name":"env","value":"prod"}],"securityProfile":{"secureBootEnabled":"true"}},"network":{"interface":[]},"extendedLocation":{"type":"edge"}}"#, "application/json"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -135,10 +265,15 @@ mod tests {
     #[tokio::test]
     async fn test_check_metadata_server_failure() {
         let mock_server = MockServer::start().await;
-        Mock::given(query_param("api-version", "2017-12-01"))
+        Mock::given(query_param("api-version", "2021-02-01"))
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 compute: Compute {
                     vm_id: "".to_string(),
+                    location: None,
+                    vm_scale_set_name: None,
+                    az_environment: None,
+                    zone: None,
+                    priority: None,
                 },
             }))
             .expect(1)
@@ -146,12 +281,166 @@ mod tests {
             .await;
 
         let provider = Azure;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_get_metadata_location() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                compute: Compute {
+                    vm_id: "vm-123abc".to_string(),
+                    location: Some("westeurope".to_string()),
+                    vm_scale_set_name: None,
+                    az_environment: None,
+                    zone: None,
+                    priority: None,
+                },
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await;
+
+        assert_eq!(
+            result.and_then(|metadata| metadata.compute.location),
+            Some("westeurope".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_availability_zone() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"compute": {"location": "eastus2", "vmId": "vm-123abc", "zone": "1"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.availability_zone(&ctx).await;
+
+        assert_eq!(result.as_deref(), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_availability_zone_unzoned() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"compute": {"location": "eastus2", "vmId": "vm-123abc", "zone": ""}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.availability_zone(&ctx).await;
+
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_is_interruptible_priority() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"compute": {"priority": "Spot", "vmId": "vm-123abc"}}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+
+        assert_eq!(provider.is_interruptible(&ctx).await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_is_interruptible_scheduled_events() {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"compute": {"vmId": "vm-123abc"}}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(query_param("api-version", "2020-07-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "DocumentIncarnation": 1,
+                    "Events": [{"EventId": "602d9444", "EventType": "Preempt", "Resources": ["vm-123abc"]}]
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Azure;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+
+        assert_eq!(provider.is_interruptible(&ctx).await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_government_cloud() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(query_param("api-version", "2021-02-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "compute": {
+                        "azEnvironment": "AzureUSGovernmentCloud",
+                        "location": "usgovvirginia",
+                        "vmId": "02aab8a4-74ef-476e-8182-f6d2ba4166a6",
+                        "vmScaleSetName": "crpteste9vflji9"
+                    }
+                }"#,
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+        let ctx = Context::new(client);
+
+        let provider = Azure;
+        assert_eq!(
+            provider.environment(&ctx).await.as_deref(),
+            Some("AzureUSGovernmentCloud")
+        );
+        assert_eq!(
+            provider.scale_set(&ctx).await.as_deref(),
+            Some("crpteste9vflji9")
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
@@ -0,0 +1,232 @@
+//! Civo.
+//!
+//! Civo serves its metadata at the same address and under the same `/latest/meta-data/` prefix as AWS, so a reachable
+//! endpoint says nothing about the provider. Instead, the metadata is only trusted if the region it reports is one of
+//! Civo's (e.g. `lon1`), which AWS regions (e.g. `us-east-1`) never are.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_REGION_PATH: &str = "/latest/meta-data/placement/region";
+const METADATA_INSTANCE_ID_PATH: &str = "/latest/meta-data/instance-id";
+/// Civo's regions. New regions must be added here to be recognized from the metadata server.
+const REGIONS: [&str; 4] = ["fra1", "lon1", "nyc1", "phx1"];
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "Civo";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Civo;
+
+pub(crate) struct Civo;
+
+#[async_trait]
+impl Provider for Civo {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Civo using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Civo");
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Civo using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(
+            METADATA_URI,
+            &[METADATA_REGION_PATH, METADATA_INSTANCE_ID_PATH],
+        )
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region (e.g. `lon1`) from the instance metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_region(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+
+    /// Retrieves the instance ID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        let url = format!(
+            "{}{METADATA_INSTANCE_ID_PATH}",
+            ctx.metadata_uri(METADATA_URI)
+        );
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .map(|instance_id| instance_id.trim().to_string())
+            .filter(|instance_id| !instance_id.is_empty())
+    }
+}
+
+impl Civo {
+    /// Tries to identify Civo via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_region(ctx, metadata_uri)
+            .await
+            .is_some_and(|region| REGIONS.contains(&region.to_lowercase().as_str()))
+    }
+
+    /// Retrieves the region from the metadata server.
+    async fn get_region(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let url = format!("{metadata_uri}{METADATA_REGION_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .map(|region| region.trim().to_string())
+    }
+
+    /// Tries to identify Civo using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_REGION_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("LON1\n"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Civo;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_aws() {
+        // AWS serves its region at the same path, and its regions are never Civo's
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/latest/api/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path(METADATA_REGION_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("us-east-1"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/latest/dynamic/instance-identity/document"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"imageId": "ami-123abc", "instanceId": "i-123abc"}"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = Civo;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_failure() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_REGION_PATH))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Civo;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_instance_id() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_INSTANCE_ID_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("b8e5a1a4-3c9d-4b7e-9f3c-2d1e0f4a6b7c\n"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Civo;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.instance_id(&ctx).await;
+
+        assert_eq!(
+            result.as_deref(),
+            Some("b8e5a1a4-3c9d-4b7e-9f3c-2d1e0f4a6b7c")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Civo")?;
+
+        let provider = Civo;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Amazon EC2")?;
+
+        let provider = Civo;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
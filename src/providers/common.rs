@@ -0,0 +1,580 @@
+//! Helpers shared between providers.
+//!
+//! Providers differ mostly in the files, URLs, headers and match strings they check, so the checks themselves live
+//! here. Every helper logs under the given provider identifier and records request failures on the [Context].
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::{RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use tokio::fs;
+use tokio::net::lookup_host;
+
+use crate::{Context, DetectionMethod, ProviderId, SignalPreference};
+
+/// Maximum time to wait for the host of a metadata server addressed by name to resolve.
+pub(crate) const DNS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Reads the given vendor file, returning `None` if it does not exist or cannot be read.
+pub(crate) async fn read_vendor_file<P: AsRef<Path>>(
+    provider: ProviderId,
+    vendor_file: P,
+) -> Option<String> {
+    tracing::trace!(
+        "Checking {} vendor file: {}",
+        provider,
+        vendor_file.as_ref().display()
+    );
+
+    if !vendor_file.as_ref().is_file() {
+        #[cfg(windows)]
+        return read_bios_registry(vendor_file.as_ref());
+        #[cfg(target_os = "macos")]
+        return read_platform_expert(vendor_file.as_ref()).await;
+        #[cfg(not(any(windows, target_os = "macos")))]
+        return None;
+    }
+
+    match fs::read_to_string(vendor_file).await {
+        Ok(content) => Some(content),
+        Err(err) => {
+            tracing::trace!("Error reading file: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Reads the registry value holding the same SMBIOS field as the given DMI vendor file.
+///
+/// Windows has no `/sys/class/dmi/id/` tree, but exposes most of the same fields under
+/// `HKLM\HARDWARE\DESCRIPTION\System\BIOS`.
+#[cfg(windows)]
+fn read_bios_registry(vendor_file: &Path) -> Option<String> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    let name = bios_registry_value(vendor_file)?;
+    tracing::trace!("Checking BIOS registry value: {}", name);
+
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(r"HARDWARE\DESCRIPTION\System\BIOS");
+    match key.and_then(|key| key.get_value::<String, _>(name)) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            tracing::trace!("Error reading registry value: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Returns the name of the BIOS registry value corresponding to the given DMI vendor file, if there is one.
+#[cfg(any(windows, test))]
+fn bios_registry_value(vendor_file: &Path) -> Option<&'static str> {
+    match vendor_file.file_name()?.to_str()? {
+        "bios_vendor" => Some("BIOSVendor"),
+        "board_vendor" => Some("BaseBoardManufacturer"),
+        "product_name" => Some("SystemProductName"),
+        "product_version" => Some("SystemVersion"),
+        "sys_vendor" => Some("SystemManufacturer"),
+        _ => None,
+    }
+}
+
+/// Reads the `IOPlatformExpertDevice` property holding the same field as the given DMI vendor file.
+///
+/// macOS has no `/sys/class/dmi/id/` tree, but exposes the manufacturer and model of the machine in the I/O Registry.
+/// On Apple hardware (including EC2 Mac instances) these describe the Mac itself, so this mostly helps to identify
+/// virtualized macOS guests.
+#[cfg(target_os = "macos")]
+async fn read_platform_expert(vendor_file: &Path) -> Option<String> {
+    let name = platform_expert_property(vendor_file)?;
+    tracing::trace!("Checking IOPlatformExpertDevice property: {}", name);
+
+    let output = tokio::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            ioreg_property(&String::from_utf8_lossy(&output.stdout), name)
+        }
+        Ok(output) => {
+            tracing::trace!("ioreg exited with status: {}", output.status);
+            None
+        }
+        Err(err) => {
+            tracing::trace!("Error running ioreg: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Returns the name of the `IOPlatformExpertDevice` property corresponding to the given DMI vendor file, if there is
+/// one.
+#[cfg(any(target_os = "macos", test))]
+fn platform_expert_property(vendor_file: &Path) -> Option<&'static str> {
+    match vendor_file.file_name()?.to_str()? {
+        "board_vendor" | "sys_vendor" => Some("manufacturer"),
+        "product_name" => Some("model"),
+        _ => None,
+    }
+}
+
+/// Extracts the value of the given property from `ioreg` output, where it is printed as `"name" = <"value">`.
+#[cfg(any(target_os = "macos", test))]
+fn ioreg_property(output: &str, name: &str) -> Option<String> {
+    let prefix = format!("\"{name}\" = ");
+
+    output.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(&prefix)?;
+        let value = value.strip_prefix('<').unwrap_or(value);
+        let value = value.strip_suffix('>').unwrap_or(value);
+
+        Some(value.trim_matches(|c| c == '"' || c == '\0').to_string())
+    })
+}
+
+/// Checks whether the given vendor file contains `needle`.
+pub(crate) async fn check_vendor_file<P: AsRef<Path>>(
+    provider: ProviderId,
+    vendor_file: P,
+    needle: &str,
+) -> bool {
+    read_vendor_file(provider, vendor_file)
+        .await
+        .is_some_and(|content| content.contains(needle))
+}
+
+/// Builds the URLs of the given paths on a metadata server, as reported by [Provider::metadata_endpoints].
+///
+/// [Provider::metadata_endpoints]: crate::Provider::metadata_endpoints
+pub(crate) fn endpoints(metadata_uri: &str, paths: &[&str]) -> Vec<Url> {
+    paths
+        .iter()
+        .map(|path| {
+            Url::parse(&format!("{metadata_uri}{path}")).expect("metadata endpoints are valid URLs")
+        })
+        .collect()
+}
+
+/// Converts the given vendor file paths, as reported by [Provider::vendor_files].
+///
+/// [Provider::vendor_files]: crate::Provider::vendor_files
+pub(crate) fn paths(vendor_files: &[&str]) -> Vec<PathBuf> {
+    vendor_files.iter().map(PathBuf::from).collect()
+}
+
+/// Builds a GET request for the given metadata URL with the given headers.
+pub(crate) fn get(ctx: &Context, url: &str, headers: &[(&str, &str)]) -> RequestBuilder {
+    headers
+        .iter()
+        .fold(ctx.client.get(url), |req, (name, value)| {
+            req.header(*name, *value)
+        })
+}
+
+/// Checks whether resolving the host of the given metadata server completes within [DNS_TIMEOUT].
+///
+/// Providers whose metadata server is addressed by name (rather than a link-local IP address) call this before making
+/// any requests. Outside the provider the name usually does not resolve, and on some networks the lookup alone could
+/// otherwise take up the whole detection timeout, as the request timeout does not bound it separately. A lookup that
+/// fails quickly (e.g. `NXDOMAIN`) is not treated as a negative match, as requests may still be routed through a proxy
+/// that resolves it.
+pub(crate) async fn resolve_with_timeout(provider: ProviderId, metadata_uri: &str) -> bool {
+    let Some(addr) = Url::parse(metadata_uri).ok().and_then(|url| {
+        Some(format!(
+            "{}:{}",
+            url.host_str()?,
+            url.port_or_known_default()?
+        ))
+    }) else {
+        tracing::trace!("Invalid {} metadata URI: {}", provider, metadata_uri);
+        return false;
+    };
+    tracing::trace!("Resolving {} metadata server: {}", provider, addr);
+
+    match tokio::time::timeout(DNS_TIMEOUT, lookup_host(addr)).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(err)) => {
+            tracing::trace!("Error resolving metadata server: {:?}", err);
+            true
+        }
+        Err(_) => {
+            tracing::trace!("Timed out resolving metadata server");
+            false
+        }
+    }
+}
+
+/// Sends the given metadata request, returning the response if it succeeded.
+///
+/// Any 2xx status counts as success, including `204 No Content`, so a reachable metadata server alone never implies a
+/// match: each provider decides that from the response. The default client follows at most [MAX_REDIRECTS](crate::MAX_REDIRECTS) redirect.
+pub(crate) async fn send(
+    ctx: &Context,
+    provider: ProviderId,
+    req: RequestBuilder,
+) -> Option<reqwest::Response> {
+    let req = match ctx.request_timeout() {
+        Some(timeout) => req.timeout(timeout),
+        None => req,
+    };
+
+    match req.send().await {
+        Ok(resp) if resp.status().is_success() => Some(resp),
+        Ok(resp) => {
+            tracing::trace!("Unexpected response status: {}", resp.status());
+            None
+        }
+        Err(err) => {
+            tracing::trace!("Error making request: {:?}", err);
+            ctx.record_error(provider, &err);
+            None
+        }
+    }
+}
+
+/// Sends the given metadata request and parses its JSON body.
+pub(crate) async fn send_json<T: DeserializeOwned>(
+    ctx: &Context,
+    provider: ProviderId,
+    req: RequestBuilder,
+) -> Option<T> {
+    match send(ctx, provider, req).await?.json::<T>().await {
+        Ok(metadata) => Some(metadata),
+        Err(err) => {
+            tracing::trace!("Error reading response: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Sends the given metadata request and returns its body as text.
+pub(crate) async fn send_text(
+    ctx: &Context,
+    provider: ProviderId,
+    req: RequestBuilder,
+) -> Option<String> {
+    match send(ctx, provider, req).await?.text().await {
+        Ok(text) => Some(text),
+        Err(err) => {
+            tracing::trace!("Error reading response: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Retrieves and parses the JSON metadata at the given URL.
+pub(crate) async fn get_metadata<T: DeserializeOwned>(
+    ctx: &Context,
+    provider: ProviderId,
+    url: &str,
+    headers: &[(&str, &str)],
+) -> Option<T> {
+    tracing::trace!("Checking {} metadata using url: {}", provider, url);
+
+    send_json(ctx, provider, get(ctx, url, headers)).await
+}
+
+/// Checks whether the JSON metadata at the given URL satisfies the predicate.
+pub(crate) async fn check_metadata<T: DeserializeOwned, F: FnOnce(T) -> bool>(
+    ctx: &Context,
+    provider: ProviderId,
+    url: &str,
+    headers: &[(&str, &str)],
+    predicate: F,
+) -> bool {
+    get_metadata(ctx, provider, url, headers)
+        .await
+        .is_some_and(predicate)
+}
+
+/// Runs a provider's vendor file and metadata server checks concurrently, returning the methods that matched.
+///
+/// Checks disabled on the [Context] are skipped. Unless the context is exhaustive, this returns as soon as either check
+/// matches and drops the other, so a slow vendor file read never delays the metadata server request (or vice versa).
+/// Under [SignalPreference::MetadataFirst], the vendor file is instead only checked once the metadata server did not
+/// match.
+pub(crate) async fn check_vendor_file_and_metadata(
+    ctx: &Context,
+    vendor_file: impl Future<Output = bool>,
+    metadata_server: impl Future<Output = bool>,
+) -> Vec<DetectionMethod> {
+    let vendor_file = async { ctx.vendor_files && vendor_file.await };
+    let metadata_server = async { ctx.metadata_server && metadata_server.await };
+
+    if ctx.exhaustive {
+        let (vendor_file, metadata_server) = tokio::join!(vendor_file, metadata_server);
+
+        return [
+            (vendor_file, DetectionMethod::VendorFile),
+            (metadata_server, DetectionMethod::MetadataServer),
+        ]
+        .into_iter()
+        .filter_map(|(matched, method)| matched.then_some(method))
+        .collect();
+    }
+
+    if ctx.signal_preference == SignalPreference::MetadataFirst {
+        if metadata_server.await {
+            return vec![DetectionMethod::MetadataServer];
+        }
+
+        return match vendor_file.await {
+            true => vec![DetectionMethod::VendorFile],
+            false => Vec::new(),
+        };
+    }
+
+    tokio::select! {
+        true = vendor_file => vec![DetectionMethod::VendorFile],
+        true = metadata_server => vec![DetectionMethod::MetadataServer],
+        else => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use serde_json::Value;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::{header, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::DetectConfig;
+
+    #[tokio::test]
+    async fn test_check_vendor_file() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Example Cloud")?;
+
+        assert!(check_vendor_file(ProviderId::Unknown, vendor_file.path(), "Example").await);
+        assert!(!check_vendor_file(ProviderId::Unknown, vendor_file.path(), "Other").await);
+        assert!(!check_vendor_file(ProviderId::Unknown, "/nonexistent", "Example").await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_and_metadata() {
+        let ctx = Context::new(Client::new());
+
+        // A match returns without waiting for the other check
+        let pending = std::future::pending::<bool>();
+        assert_eq!(
+            check_vendor_file_and_metadata(&ctx, async { true }, pending).await,
+            vec![DetectionMethod::VendorFile]
+        );
+        let pending = std::future::pending::<bool>();
+        assert_eq!(
+            check_vendor_file_and_metadata(&ctx, pending, async { true }).await,
+            vec![DetectionMethod::MetadataServer]
+        );
+        assert!(
+            check_vendor_file_and_metadata(&ctx, async { false }, async { false })
+                .await
+                .is_empty()
+        );
+
+        let ctx = Context::exhaustive(Client::new());
+        assert_eq!(
+            check_vendor_file_and_metadata(&ctx, async { true }, async { true }).await,
+            vec![DetectionMethod::VendorFile, DetectionMethod::MetadataServer]
+        );
+
+        // The vendor file is only checked if the metadata server did not match
+        let ctx = Context {
+            signal_preference: SignalPreference::MetadataFirst,
+            ..Context::new(Client::new())
+        };
+        let pending = std::future::pending::<bool>();
+        assert_eq!(
+            check_vendor_file_and_metadata(&ctx, pending, async { true }).await,
+            vec![DetectionMethod::MetadataServer]
+        );
+        assert_eq!(
+            check_vendor_file_and_metadata(&ctx, async { true }, async { false }).await,
+            vec![DetectionMethod::VendorFile]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_request_timeout_within_deadline() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(3)))
+            .mount(&mock_server)
+            .await;
+
+        // The outer budget is 1s, while the client itself would wait for 5s
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let config = DetectConfig::new().total_timeout(Duration::from_secs(1));
+        let ctx = Context::with_config(client, &config);
+        let url = format!("{}/slow", mock_server.uri());
+
+        let start = Instant::now();
+        let result = send(&ctx, ProviderId::Unknown, get(&ctx, &url, &[])).await;
+
+        assert!(result.is_none());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(ctx.errors().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_redirect() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/redirect"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/metadata"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/redirect-twice"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/redirect"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/metadata"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "i-123abc"})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let ctx = Context::new(crate::build_client(crate::DEFAULT_USER_AGENT, None).unwrap());
+        let check = |url: String| {
+            let ctx = ctx.clone();
+            async move {
+                check_metadata(&ctx, ProviderId::Unknown, &url, &[], |metadata: Value| {
+                    metadata["id"] == "i-123abc"
+                })
+                .await
+            }
+        };
+
+        // A single redirect is followed, but a longer chain is not
+        assert!(check(format!("{}/redirect", mock_server.uri())).await);
+        assert!(!check(format!("{}/redirect-twice", mock_server.uri())).await);
+    }
+
+    #[tokio::test]
+    async fn test_send_no_content() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/metadata"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let ctx = Context::new(Client::new());
+        let url = format!("{}/metadata", mock_server.uri());
+
+        // The server is reachable, but there is no metadata to match
+        assert!(send(&ctx, ProviderId::Unknown, get(&ctx, &url, &[]))
+            .await
+            .is_some());
+        assert!(!check_metadata(&ctx, ProviderId::Unknown, &url, &[], |_: Value| true).await);
+        assert!(ctx.errors().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_with_timeout() {
+        let start = std::time::Instant::now();
+
+        // IP addresses need no lookup, and a name that does not exist fails fast rather than timing out
+        assert!(resolve_with_timeout(ProviderId::Unknown, "http://169.254.169.254").await);
+        assert!(
+            resolve_with_timeout(ProviderId::Unknown, "http://metadata.cloud-detect.invalid").await
+        );
+        assert!(start.elapsed() < DNS_TIMEOUT * 2);
+
+        assert!(!resolve_with_timeout(ProviderId::Unknown, "not a uri").await);
+    }
+
+    #[test]
+    fn test_endpoints() {
+        assert_eq!(
+            endpoints(
+                "http://169.254.169.254",
+                &["/v1.json", "/metadata?api-version=1"]
+            ),
+            vec![
+                Url::parse("http://169.254.169.254/v1.json").unwrap(),
+                Url::parse("http://169.254.169.254/metadata?api-version=1").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bios_registry_value() {
+        assert_eq!(
+            bios_registry_value(Path::new("/sys/class/dmi/id/sys_vendor")),
+            Some("SystemManufacturer")
+        );
+        assert_eq!(
+            bios_registry_value(Path::new("/sys/class/dmi/id/product_name")),
+            Some("SystemProductName")
+        );
+        assert_eq!(
+            bios_registry_value(Path::new("/sys/class/dmi/id/chassis_asset_tag")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ioreg_property() {
+        let output = r#"+-o J316sAP  <class IOPlatformExpertDevice, id 0x100000210, registered, matched, active>
+    {
+      "IOPlatformSerialNumber" = "ABC123"
+      "manufacturer" = <"Apple Inc.">
+      "model" = <"Macmini9,1">
+    }"#;
+
+        assert_eq!(
+            platform_expert_property(Path::new("/sys/class/dmi/id/sys_vendor"))
+                .and_then(|name| ioreg_property(output, name)),
+            Some("Apple Inc.".to_string())
+        );
+        assert_eq!(
+            ioreg_property(output, "model"),
+            Some("Macmini9,1".to_string())
+        );
+        assert_eq!(ioreg_property(output, "version"), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/metadata"))
+            .and(header("Metadata", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "abc"}"#))
+            .mount(&mock_server)
+            .await;
+
+        let ctx = Context::new(Client::new());
+        let url = format!("{}/metadata", mock_server.uri());
+        let headers = [("Metadata", "true")];
+
+        assert!(
+            check_metadata(
+                &ctx,
+                ProviderId::Unknown,
+                &url,
+                &headers,
+                |metadata: Value| { metadata["id"] == "abc" }
+            )
+            .await
+        );
+        assert!(
+            !check_metadata(&ctx, ProviderId::Unknown, &url, &[], |_: Value| true).await,
+            "requests without the expected headers should not match"
+        );
+    }
+}
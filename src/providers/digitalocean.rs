@@ -1,17 +1,27 @@
 //! DigitalOcean.
+//!
+//! Droplets are identified by their vendor file or the droplet metadata server. App Platform containers have access to
+//! neither, so as a fallback they are identified by the `APP_*` environment variables App Platform sets for every
+//! component.
 
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/metadata/v1.json";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "DigitalOcean";
+const ENV_VARS: [&str; 2] = ["APP_ID", "APP_DOMAIN"];
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::DigitalOcean;
 
 pub(crate) struct DigitalOcean;
@@ -19,6 +29,8 @@ pub(crate) struct DigitalOcean;
 #[derive(Serialize, Deserialize)]
 struct MetadataResponse {
     droplet_id: usize,
+    #[serde(default)]
+    region: Option<String>,
 }
 
 #[async_trait]
@@ -28,69 +40,94 @@ impl Provider for DigitalOcean {
     }
 
     /// Tries to identify DigitalOcean using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking DigitalOcean");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified DigitalOcean");
-            let res = tx.send(IDENTIFIER).await;
+        let mut methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+        if (ctx.exhaustive || methods.is_empty()) && self.check_environment(&ENV_VARS) {
+            methods.push(DetectionMethod::Environment);
+        }
+
+        for method in methods {
+            tracing::trace!("Identified DigitalOcean using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region from the droplet metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region
+    }
+
+    /// Retrieves the droplet ID from the droplet metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.droplet_id.to_string())
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl DigitalOcean {
     /// Tries to identify DigitalOcean via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
         let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
-
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-
-        match client.get(url).send().await {
-            Ok(resp) => match resp.json::<MetadataResponse>().await {
-                Ok(resp) => resp.droplet_id > 0,
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
+
+        common::check_metadata(ctx, IDENTIFIER, &url, &[], |metadata: MetadataResponse| {
+            metadata.droplet_id > 0
+        })
+        .await
+    }
+
+    /// Retrieves the droplet metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
     }
 
     /// Tries to identify DigitalOcean using vendor file(s).
     async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+
+    /// Tries to identify DigitalOcean App Platform using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
         tracing::trace!(
-            "Checking {} vendor file: {}",
+            "Checking {} environment variables: {:?}",
             IDENTIFIER,
-            vendor_file.as_ref().display()
+            env_vars
         );
 
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("DigitalOcean"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
-
-        false
+        env_vars
+            .iter()
+            .all(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
     }
 }
 
@@ -99,6 +136,7 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
     use wiremock::matchers::path;
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -109,16 +147,18 @@ mod tests {
     async fn test_check_metadata_server_success() {
         let mock_server = MockServer::start().await;
         Mock::given(path(METADATA_PATH))
-            .respond_with(
-                ResponseTemplate::new(200).set_body_json(MetadataResponse { droplet_id: 123 }),
-            )
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                droplet_id: 123,
+                region: None,
+            }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = DigitalOcean;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -127,20 +167,71 @@ mod tests {
     async fn test_check_metadata_server_failure() {
         let mock_server = MockServer::start().await;
         Mock::given(path(METADATA_PATH))
-            .respond_with(
-                ResponseTemplate::new(200).set_body_json(MetadataResponse { droplet_id: 0 }),
-            )
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                droplet_id: 0,
+                region: None,
+            }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = DigitalOcean;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_check_metadata_server_upcloud() {
+        // UpCloud serves its server metadata at the same path, without a droplet ID
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "cloud_name": "upcloud",
+                    "instance_id": "00133099-f1fd-4ed2-b1c7-d027eb43a8f5",
+                    "region": "fi-hel1"
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = DigitalOcean;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_region() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                droplet_id: 123,
+                region: Some("nyc3".to_string()),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = DigitalOcean;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await;
+
+        assert_eq!(
+            result.and_then(|metadata| metadata.region),
+            Some("nyc3".to_string())
+        );
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
@@ -165,4 +256,39 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_DO_APP_ID", "4f6c71e2");
+        env::set_var(
+            "CLOUD_DETECT_TEST_DO_APP_DOMAIN",
+            "sample-app.ondigitalocean.app",
+        );
+
+        let provider = DigitalOcean;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_DO_APP_ID",
+            "CLOUD_DETECT_TEST_DO_APP_DOMAIN",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_DO_APP_ID");
+        env::remove_var("CLOUD_DETECT_TEST_DO_APP_DOMAIN");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        // Other platforms may set a generic `APP_DOMAIN`, so it alone is not enough
+        env::set_var("CLOUD_DETECT_TEST_DO_APP_DOMAIN_ONLY", "example.com");
+
+        let provider = DigitalOcean;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_DO_APP_ID_UNSET",
+            "CLOUD_DETECT_TEST_DO_APP_DOMAIN_ONLY",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_DO_APP_DOMAIN_ONLY");
+        assert!(!result);
+    }
 }
@@ -0,0 +1,205 @@
+//! Equinix Metal.
+//!
+//! Unlike most providers, Equinix Metal serves its metadata over HTTPS from a DNS name rather than a link-local IP.
+//! The crate enables reqwest's `rustls-tls` backend, so the shared client handles it without further configuration.
+//! As with GCP, the name is resolved with a bounded timeout before any request is made, so that a slow lookup outside
+//! Equinix Metal cannot use up the detection timeout.
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "https://metadata.platformequinix.com";
+const METADATA_PATH: &str = "/metadata";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::EquinixMetal;
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    id: String,
+    plan: String,
+    #[serde(default)]
+    metro: Option<String>,
+}
+
+pub(crate) struct EquinixMetal;
+
+#[async_trait]
+impl Provider for EquinixMetal {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Equinix Metal using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Equinix Metal");
+        let method = if ctx.metadata_server
+            && self
+                .check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI))
+                .await
+        {
+            Some(DetectionMethod::MetadataServer)
+        } else {
+            None
+        };
+
+        if let Some(method) = method {
+            tracing::trace!("Identified Equinix Metal using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    /// Retrieves the metro from the device metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .metro
+    }
+
+    /// Retrieves the device ID from the device metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.id)
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+}
+
+impl EquinixMetal {
+    /// Tries to identify Equinix Metal via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        if !common::resolve_with_timeout(IDENTIFIER, metadata_uri).await {
+            return false;
+        }
+
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::check_metadata(ctx, IDENTIFIER, &url, &[], |metadata: MetadataResponse| {
+            !metadata.id.is_empty() && !metadata.plan.is_empty()
+        })
+        .await
+    }
+
+    /// Retrieves the device metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                id: "7a2b5c1e-0d4f-4e3a-9b8c-1f2e3d4c5b6a".to_string(),
+                plan: "c3.small.x86".to_string(),
+                metro: Some("da".to_string()),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = EquinixMetal;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_failure() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = EquinixMetal;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_metro() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                id: "7a2b5c1e-0d4f-4e3a-9b8c-1f2e3d4c5b6a".to_string(),
+                plan: "c3.small.x86".to_string(),
+                metro: Some("da".to_string()),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = EquinixMetal;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await;
+
+        assert_eq!(
+            result.and_then(|metadata| metadata.metro),
+            Some("da".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_unresolvable() {
+        let provider = EquinixMetal;
+        let ctx = Context::new(Client::new());
+        let start = std::time::Instant::now();
+        // The `.invalid` TLD is reserved, so this never resolves
+        let result = provider
+            .check_metadata_server(&ctx, "https://metadata.cloud-detect.invalid")
+            .await;
+
+        assert!(!result);
+        assert!(start.elapsed() < common::DNS_TIMEOUT * 2);
+    }
+
+    #[test]
+    fn test_https_client() {
+        // The metadata endpoint requires TLS, which must be available to the default client
+        let client = Client::builder().use_rustls_tls().https_only(true).build();
+
+        assert!(client.is_ok());
+    }
+}
@@ -0,0 +1,234 @@
+//! Exoscale.
+//!
+//! Exoscale runs on CloudStack, which serves its metadata at the same address and under the same `/latest/meta-data/`
+//! prefix as AWS, so a reachable endpoint says nothing about the provider. Instead, the metadata is only trusted if the
+//! availability zone it reports is one of Exoscale's (e.g. `ch-gva-2`), which AWS zones (e.g. `us-east-1a`) never
+//! are. The service offering (e.g. `standard.medium`) is not used, as it does not name Exoscale.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_ZONE_PATH: &str = "/latest/meta-data/availability-zone";
+const METADATA_INSTANCE_ID_PATH: &str = "/latest/meta-data/instance-id";
+/// Exoscale's zones. New zones must be added here to be recognized from the metadata server.
+const ZONES: [&str; 7] = [
+    "at-vie-1", "at-vie-2", "bg-sof-1", "ch-dk-2", "ch-gva-2", "de-fra-1", "de-muc-1",
+];
+const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const PRODUCT_NAME: &str = "Exoscale";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Exoscale;
+
+pub(crate) struct Exoscale;
+
+#[async_trait]
+impl Provider for Exoscale {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Exoscale using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Exoscale");
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(PRODUCT_NAME_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Exoscale using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(
+            METADATA_URI,
+            &[METADATA_ZONE_PATH, METADATA_INSTANCE_ID_PATH],
+        )
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[PRODUCT_NAME_FILE])
+    }
+
+    /// Retrieves the zone (e.g. `ch-gva-2`) from the instance metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_zone(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+
+    /// Retrieves the instance ID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        let url = format!(
+            "{}{METADATA_INSTANCE_ID_PATH}",
+            ctx.metadata_uri(METADATA_URI)
+        );
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .map(|instance_id| instance_id.trim().to_string())
+            .filter(|instance_id| !instance_id.is_empty())
+    }
+}
+
+impl Exoscale {
+    /// Tries to identify Exoscale via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_zone(ctx, metadata_uri)
+            .await
+            .is_some_and(|zone| ZONES.contains(&zone.as_str()))
+    }
+
+    /// Retrieves the availability zone from the metadata server.
+    async fn get_zone(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let url = format!("{metadata_uri}{METADATA_ZONE_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .map(|zone| zone.trim().to_string())
+    }
+
+    /// Tries to identify Exoscale using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, PRODUCT_NAME).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ZONE_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ch-gva-2"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Exoscale;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_aws() {
+        // AWS serves its availability zone under `placement/`, and its zones are never Exoscale's
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/latest/api/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/latest/meta-data/placement/availability-zone"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("eu-central-1a"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/latest/dynamic/instance-identity/document"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"imageId": "ami-123abc", "instanceId": "i-123abc"}"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = Exoscale;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_other_zone() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ZONE_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("us-east-1a"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Exoscale;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_instance_id() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_INSTANCE_ID_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("8ae3bd78-8dd2-4eb2-9c8e-7bd3ab7b1f98\n"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Exoscale;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.instance_id(&ctx).await;
+
+        assert_eq!(
+            result.as_deref(),
+            Some("8ae3bd78-8dd2-4eb2-9c8e-7bd3ab7b1f98")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Exoscale Compute Platform")?;
+
+        let provider = Exoscale;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"HVM domU")?;
+
+        let provider = Exoscale;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
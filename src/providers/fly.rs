@@ -0,0 +1,96 @@
+//! Fly.io.
+//!
+//! Fly Machines run in Firecracker microVMs, which expose neither a recognizable vendor file nor a standard metadata
+//! server. Instead, Fly.io sets `FLY_*` environment variables in every Machine.
+
+use std::env;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const ENV_VARS: [&str; 2] = ["FLY_ALLOC_ID", "FLY_APP_NAME"];
+const REGION_ENV_VAR: &str = "FLY_REGION";
+const INSTANCE_ID_ENV_VAR: &str = "FLY_MACHINE_ID";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::FlyIo;
+
+pub(crate) struct FlyIo;
+
+#[async_trait]
+impl Provider for FlyIo {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Fly.io using all the implemented options.
+    async fn identify(&self, _ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Fly.io");
+        if self.check_environment(&ENV_VARS) {
+            let method = DetectionMethod::Environment;
+            tracing::trace!("Identified Fly.io using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Retrieves the region from the environment.
+    async fn region(&self, _ctx: &Context) -> Option<String> {
+        env::var(REGION_ENV_VAR).ok()
+    }
+
+    /// Retrieves the Machine ID from the environment.
+    async fn instance_id(&self, _ctx: &Context) -> Option<String> {
+        env::var(INSTANCE_ID_ENV_VAR).ok()
+    }
+}
+
+impl FlyIo {
+    /// Tries to identify Fly.io using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .any(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_FLY_APP_NAME", "my-app");
+
+        let provider = FlyIo;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_FLY_ALLOC_ID",
+            "CLOUD_DETECT_TEST_FLY_APP_NAME",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_FLY_APP_NAME");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        env::set_var("CLOUD_DETECT_TEST_FLY_EMPTY", "");
+
+        let provider = FlyIo;
+        let result = provider
+            .check_environment(&["CLOUD_DETECT_TEST_FLY_EMPTY", "CLOUD_DETECT_TEST_FLY_UNSET"]);
+
+        env::remove_var("CLOUD_DETECT_TEST_FLY_EMPTY");
+        assert!(!result);
+    }
+}
@@ -1,16 +1,31 @@
 //! Google Cloud Platform (GCP).
+//!
+//! Compute Engine instances are identified by their vendor file or the instance attributes on the metadata server.
+//! Serverless platforms (Cloud Run, App Engine and Cloud Functions) serve neither, but do answer on the metadata server
+//! root, and set environment variables such as `K_SERVICE` (Cloud Run) and `GAE_ENV` (App Engine).
 
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
-use tokio::fs;
+use reqwest::Url;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://metadata.google.internal";
+pub(crate) const METADATA_URI: &str = "http://metadata.google.internal";
 const METADATA_PATH: &str = "/computeMetadata/v1/instance/tags";
+const METADATA_ROOT_PATH: &str = "/computeMetadata/v1/";
+const METADATA_ZONE_PATH: &str = "/computeMetadata/v1/instance/zone";
+const METADATA_ID_PATH: &str = "/computeMetadata/v1/instance/id";
+const METADATA_PREEMPTIBLE_PATH: &str = "/computeMetadata/v1/instance/scheduling/preemptible";
+const METADATA_INSTANCE_PATH: &str = "/computeMetadata/v1/instance/?recursive=true&alt=json";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const PRODUCT_NAME: &str = "Google";
+const ENV_VARS: [&str; 2] = ["K_SERVICE", "GAE_ENV"];
+const HEADERS: [(&str, &str); 1] = [("Metadata-Flavor", "Google")];
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::GCP;
 
 pub(crate) struct Gcp;
@@ -22,66 +37,167 @@ impl Provider for Gcp {
     }
 
     /// Tries to identify GCP using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Google Cloud Platform");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified Google Cloud Platform");
-            let res = tx.send(IDENTIFIER).await;
+        let mut methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+        if (ctx.exhaustive || methods.is_empty()) && self.check_environment(&ENV_VARS) {
+            methods.push(DetectionMethod::Environment);
+        }
+
+        for method in methods {
+            tracing::trace!("Identified Google Cloud Platform using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(
+            METADATA_URI,
+            &[
+                METADATA_PATH,
+                METADATA_ROOT_PATH,
+                METADATA_ZONE_PATH,
+                METADATA_ID_PATH,
+                METADATA_INSTANCE_PATH,
+            ],
+        )
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region from the zone the instance runs in.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        let zone = self.get_zone(ctx, ctx.metadata_uri(METADATA_URI)).await?;
+
+        zone.rsplit_once('-').map(|(region, _)| region.to_string())
+    }
+
+    /// Retrieves the zone (e.g. `us-central1-b`) the instance runs in.
+    async fn availability_zone(&self, ctx: &Context) -> Option<String> {
+        self.get_zone(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+
+    /// Retrieves the numeric instance ID from the metadata server.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_instance_id(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+    }
+
+    /// Retrieves whether the instance is preemptible (`TRUE` or `FALSE`) from the metadata server.
+    async fn is_interruptible(&self, ctx: &Context) -> Option<bool> {
+        let url = format!(
+            "{}{METADATA_PREEMPTIBLE_PATH}",
+            ctx.metadata_uri(METADATA_URI)
+        );
+        tracing::trace!("Retrieving {} scheduling using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &HEADERS))
+            .await
+            .map(|preemptible| preemptible.trim().eq_ignore_ascii_case("true"))
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.fetch_full_metadata(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+    }
 }
 
 impl Gcp {
+    /// Retrieves every instance attribute from the metadata server in a single request.
+    ///
+    /// The recursive query returns the whole instance tree (attributes, network interfaces, service accounts etc.)
+    /// as one JSON document, rather than a directory listing to walk one key at a time.
+    async fn fetch_full_metadata(&self, ctx: &Context, metadata_uri: &str) -> Option<Value> {
+        let url = format!("{metadata_uri}{METADATA_INSTANCE_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &HEADERS).await
+    }
+
+    /// Retrieves the zone (e.g. `us-central1-a`) of the instance from the metadata server.
+    async fn get_zone(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let url = format!("{metadata_uri}{METADATA_ZONE_PATH}");
+        tracing::trace!("Retrieving {} zone using url: {}", IDENTIFIER, url);
+
+        // The zone is returned in the form `projects/<project-number>/zones/<zone>`.
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &HEADERS))
+            .await
+            .and_then(|text| text.rsplit('/').next().map(|zone| zone.trim().to_string()))
+    }
+
+    /// Retrieves the instance ID from the metadata server.
+    async fn get_instance_id(&self, ctx: &Context, metadata_uri: &str) -> Option<String> {
+        let url = format!("{metadata_uri}{METADATA_ID_PATH}");
+        tracing::trace!("Retrieving {} instance ID using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &HEADERS))
+            .await
+            .map(|text| text.trim().to_string())
+    }
+
     /// Tries to identify GCP via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        // Unlike most providers, the metadata server is addressed by name
+        if !common::resolve_with_timeout(IDENTIFIER, metadata_uri).await {
+            return false;
+        }
+
         let url = format!("{metadata_uri}{METADATA_PATH}");
         tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
 
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
+        if common::send(ctx, IDENTIFIER, common::get(ctx, &url, &HEADERS))
+            .await
+            .is_some()
+        {
+            return true;
+        }
 
-        let req = client.get(url).header("Metadata-Flavor", "Google");
-        let resp = req.send().await;
+        self.check_metadata_root(ctx, metadata_uri).await
+    }
 
-        match resp {
-            Ok(resp) => resp.status().is_success(),
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
+    /// Tries to identify GCP serverless platforms via the metadata server root, which they serve without instance
+    /// attributes.
+    async fn check_metadata_root(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        let url = format!("{metadata_uri}{METADATA_ROOT_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        // The metadata server marks its responses, which a generic server answering any path would not
+        common::send(ctx, IDENTIFIER, common::get(ctx, &url, &HEADERS))
+            .await
+            .is_some_and(|resp| {
+                resp.headers()
+                    .get("Metadata-Flavor")
+                    .is_some_and(|value| value == "Google")
+            })
     }
 
-    /// Tries to identify GCP using vendor file(s).
-    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+    /// Tries to identify GCP serverless platforms using environment variable(s).
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
         tracing::trace!(
-            "Checking {} vendor file: {}",
+            "Checking {} environment variables: {:?}",
             IDENTIFIER,
-            vendor_file.as_ref().display()
+            env_vars
         );
 
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("Google"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
+        env_vars
+            .iter()
+            .any(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
 
-        false
+    /// Tries to identify GCP using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, PRODUCT_NAME).await
     }
 }
 
@@ -90,8 +206,10 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
-    use wiremock::matchers::path;
+    use tokio::sync::mpsc;
+    use wiremock::matchers::{header, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
@@ -106,8 +224,9 @@ mod tests {
             .await;
 
         let provider = Gcp;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -122,12 +241,254 @@ mod tests {
             .await;
 
         let provider = Gcp;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_check_metadata_server_root() {
+        let mock_server = MockServer::start().await;
+        // Serverless platforms serve no instance attributes, so `instance/tags` responds with 404
+        Mock::given(path(METADATA_ROOT_PATH))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(ResponseTemplate::new(200).insert_header("Metadata-Flavor", "Google"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_root_without_flavor() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ROOT_PATH))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_root(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_K_SERVICE", "hello");
+
+        let provider = Gcp;
+        let result = provider
+            .check_environment(&["CLOUD_DETECT_TEST_K_SERVICE", "CLOUD_DETECT_TEST_GAE_ENV"]);
+
+        env::remove_var("CLOUD_DETECT_TEST_K_SERVICE");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = Gcp;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_K_SERVICE_UNSET",
+            "CLOUD_DETECT_TEST_GAE_ENV_UNSET",
+        ]);
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_unresolvable() {
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let start = std::time::Instant::now();
+        // The `.invalid` TLD is reserved, so this never resolves
+        let result = provider
+            .check_metadata_server(&ctx, "http://metadata.cloud-detect.invalid")
+            .await;
+
+        assert!(!result);
+        assert!(start.elapsed() < common::DNS_TIMEOUT * 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_zone() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ZONE_PATH))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("projects/123456/zones/us-central1-a"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.get_zone(&ctx, &metadata_uri).await;
+
+        assert_eq!(result.as_deref(), Some("us-central1-a"));
+    }
+
+    #[tokio::test]
+    async fn test_availability_zone() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ZONE_PATH))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("projects/123456/zones/us-central1-b"),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+
+        assert_eq!(
+            provider.availability_zone(&ctx).await.as_deref(),
+            Some("us-central1-b")
+        );
+        assert_eq!(provider.region(&ctx).await.as_deref(), Some("us-central1"));
+    }
+
+    #[tokio::test]
+    async fn test_is_interruptible() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PREEMPTIBLE_PATH))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("TRUE"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+
+        assert_eq!(provider.is_interruptible(&ctx).await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_id() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_ID_PATH))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("1234567890123456789"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.get_instance_id(&ctx, &metadata_uri).await;
+
+        assert_eq!(result.as_deref(), Some("1234567890123456789"));
+    }
+
+    #[tokio::test]
+    async fn test_identify_network_only() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let provider = Gcp;
+        provider.identify(&Context::network_only(client), tx).await;
+
+        let detection = rx.recv().await.expect("detection");
+        assert_eq!(detection.method, DetectionMethod::MetadataServer);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_metadata() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/computeMetadata/v1/instance/"))
+            .and(query_param("recursive", "true"))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"machineType": "projects/123/machineTypes/e2-medium"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Gcp;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.fetch_full_metadata(&ctx, &metadata_uri).await;
+
+        assert_eq!(
+            result
+                .as_ref()
+                .and_then(|metadata| metadata["machineType"].as_str()),
+            Some("projects/123/machineTypes/e2-medium")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_full_metadata_nested() -> Result<()> {
+        let metadata = serde_json::json!({
+            "attributes": {"ssh-keys": "user:ssh-ed25519 AAAA"},
+            "id": 4520031799277581759_u64,
+            "networkInterfaces": [{
+                "accessConfigs": [{"externalIp": "203.0.113.7", "type": "ONE_TO_ONE_NAT"}],
+                "ip": "10.128.0.2",
+                "network": "projects/123/networks/default"
+            }],
+            "serviceAccounts": {
+                "default": {"email": "123-compute@developer.gserviceaccount.com", "scopes": ["cloud-platform"]}
+            },
+            "zone": "projects/123/zones/us-central1-a"
+        });
+
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/computeMetadata/v1/instance/"))
+            .and(query_param("recursive", "true"))
+            .and(query_param("alt", "json"))
+            .and(header("Metadata-Flavor", "Google"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&metadata))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+
+        let provider = Gcp;
+        let result = provider.raw_metadata(&Context::new(client)).await;
+
+        assert_eq!(result, Some(metadata));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
@@ -0,0 +1,137 @@
+//! Heroku.
+//!
+//! Heroku dynos run on top of AWS, so Heroku is orthogonal to the underlying cloud. It is therefore only reported
+//! alongside other matches by [detect_all](crate::detect_all), and never wins first-match detection.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const ENV_VAR: &str = "DYNO";
+const MARKER_DIR: &str = "/etc/heroku";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Heroku;
+
+pub(crate) struct Heroku;
+
+#[async_trait]
+impl Provider for Heroku {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    fn is_orthogonal(&self) -> bool {
+        true
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[MARKER_DIR])
+    }
+
+    /// Tries to identify Heroku using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Heroku");
+        let mut methods = Vec::new();
+        if self.check_environment(ENV_VAR) {
+            methods.push(DetectionMethod::Environment);
+        }
+        if ctx.vendor_files
+            && (ctx.exhaustive || methods.is_empty())
+            && self.check_marker_dir(MARKER_DIR)
+        {
+            methods.push(DetectionMethod::VendorFile);
+        }
+
+        for method in methods {
+            tracing::trace!("Identified Heroku using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Retrieves the dyno name (e.g. `web.1`) from the environment.
+    async fn instance_id(&self, _ctx: &Context) -> Option<String> {
+        env::var(ENV_VAR).ok()
+    }
+}
+
+impl Heroku {
+    /// Tries to identify Heroku using the dyno environment variable.
+    fn check_environment(&self, env_var: &str) -> bool {
+        tracing::trace!("Checking {} environment variable: {}", IDENTIFIER, env_var);
+
+        env::var(env_var).is_ok_and(|value| !value.is_empty())
+    }
+
+    /// Tries to identify Heroku using the marker directory present in every dyno.
+    fn check_marker_dir<P: AsRef<Path>>(&self, marker_dir: P) -> bool {
+        tracing::trace!(
+            "Checking {} marker directory: {}",
+            IDENTIFIER,
+            marker_dir.as_ref().display()
+        );
+
+        marker_dir.as_ref().is_dir()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use a variable only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_DYNO", "web.1");
+
+        let provider = Heroku;
+        let result = provider.check_environment("CLOUD_DETECT_TEST_DYNO");
+
+        env::remove_var("CLOUD_DETECT_TEST_DYNO");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = Heroku;
+        let result = provider.check_environment("CLOUD_DETECT_TEST_DYNO_UNSET");
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_marker_dir_success() -> Result<()> {
+        let dir = TempDir::new()?;
+        let marker_dir = dir.path().join("heroku");
+        std::fs::create_dir(&marker_dir)?;
+
+        let provider = Heroku;
+        let result = provider.check_marker_dir(&marker_dir);
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_marker_dir_failure() -> Result<()> {
+        let dir = TempDir::new()?;
+
+        let provider = Heroku;
+        let result = provider.check_marker_dir(dir.path().join("heroku"));
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+//! IBM Cloud.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/metadata/v1/instance";
+const METADATA_TOKEN_PATH: &str = "/instance_identity/v1/token";
+const METADATA_VERSION: &str = "2022-03-01";
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "IBM";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::IBM;
+
+#[derive(Serialize, Deserialize)]
+struct TokenRequest {
+    expires_in: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    id: String,
+    crn: String,
+}
+
+pub(crate) struct Ibm;
+
+#[async_trait]
+impl Provider for Ibm {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify IBM Cloud using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking IBM Cloud");
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified IBM Cloud using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_TOKEN_PATH, METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the instance ID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.id)
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+}
+
+impl Ibm {
+    /// Tries to identify IBM Cloud via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .is_some_and(|metadata| {
+                !metadata.id.is_empty() && metadata.crn.starts_with("crn:v1:bluemix:")
+            })
+    }
+
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let token_url = format!("{metadata_uri}{METADATA_TOKEN_PATH}?version={METADATA_VERSION}");
+        tracing::trace!("Retrieving {} token from: {}", IDENTIFIER, token_url);
+
+        let token_req = ctx
+            .client
+            .put(token_url)
+            .header("Metadata-Flavor", "ibm")
+            .json(&TokenRequest { expires_in: 300 });
+        let token = common::send_json::<TokenResponse>(ctx, IDENTIFIER, token_req)
+            .await?
+            .access_token;
+
+        if token.is_empty() {
+            tracing::trace!("Token is empty");
+            return None;
+        }
+
+        // Request to use the token to get metadata
+        let metadata_url = format!("{metadata_uri}{METADATA_PATH}?version={METADATA_VERSION}");
+        tracing::trace!(
+            "Checking {} metadata using url: {}",
+            IDENTIFIER,
+            metadata_url
+        );
+
+        let req = ctx.client.get(metadata_url).bearer_auth(token);
+        common::send_json(ctx, IDENTIFIER, req).await
+    }
+
+    /// Tries to identify IBM Cloud using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path(METADATA_TOKEN_PATH))
+            .and(query_param("version", METADATA_VERSION))
+            .and(header("Metadata-Flavor", "ibm"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(TokenResponse {
+                access_token: "123abc".to_string(),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .and(query_param("version", METADATA_VERSION))
+            .and(header("Authorization", "Bearer 123abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                id: "0717_abc123".to_string(),
+                crn: "crn:v1:bluemix:public:is:us-south-1:a/123::instance:0717_abc123".to_string(),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Ibm;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(path(METADATA_TOKEN_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(TokenResponse {
+                access_token: "123abc".to_string(),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                id: "abc".to_string(),
+                crn: "abc".to_string(),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Ibm;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"IBM")?;
+
+        let provider = Ibm;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Ibm;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
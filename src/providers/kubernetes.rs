@@ -0,0 +1,130 @@
+//! Kubernetes.
+//!
+//! Kubernetes is orthogonal to the underlying cloud, as a pod may run on any provider (or none). It is therefore only
+//! reported alongside other matches by [detect_all](crate::detect_all), and never wins first-match detection.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const ENV_VAR: &str = "KUBERNETES_SERVICE_HOST";
+const TOKEN_FILE: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Kubernetes;
+
+pub(crate) struct Kubernetes;
+
+#[async_trait]
+impl Provider for Kubernetes {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    fn is_orthogonal(&self) -> bool {
+        true
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[TOKEN_FILE])
+    }
+
+    /// Tries to identify Kubernetes using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Kubernetes");
+        let mut methods = Vec::new();
+        if self.check_environment(ENV_VAR) {
+            methods.push(DetectionMethod::Environment);
+        }
+        if ctx.vendor_files
+            && (ctx.exhaustive || methods.is_empty())
+            && self.check_token_file(TOKEN_FILE)
+        {
+            methods.push(DetectionMethod::VendorFile);
+        }
+
+        for method in methods {
+            tracing::trace!("Identified Kubernetes using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+}
+
+impl Kubernetes {
+    /// Tries to identify Kubernetes using the service environment variable.
+    fn check_environment(&self, env_var: &str) -> bool {
+        tracing::trace!("Checking {} environment variable: {}", IDENTIFIER, env_var);
+
+        env::var(env_var).is_ok_and(|value| !value.is_empty())
+    }
+
+    /// Tries to identify Kubernetes using the mounted service account token.
+    fn check_token_file<P: AsRef<Path>>(&self, token_file: P) -> bool {
+        tracing::trace!(
+            "Checking {} token file: {}",
+            IDENTIFIER,
+            token_file.as_ref().display()
+        );
+
+        token_file.as_ref().is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::{NamedTempFile, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use a variable only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_KUBERNETES_SERVICE_HOST", "10.96.0.1");
+
+        let provider = Kubernetes;
+        let result = provider.check_environment("CLOUD_DETECT_TEST_KUBERNETES_SERVICE_HOST");
+
+        env::remove_var("CLOUD_DETECT_TEST_KUBERNETES_SERVICE_HOST");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        let provider = Kubernetes;
+        let result = provider.check_environment("CLOUD_DETECT_TEST_KUBERNETES_UNSET");
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_check_token_file_success() -> Result<()> {
+        let token_file = NamedTempFile::new()?;
+
+        let provider = Kubernetes;
+        let result = provider.check_token_file(token_file.path());
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_token_file_failure() -> Result<()> {
+        let dir = TempDir::new()?;
+
+        let provider = Kubernetes;
+        let result = provider.check_token_file(dir.path().join("token"));
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
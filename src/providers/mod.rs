@@ -1,6 +1,11 @@
 //! Provider modules.
+//!
+//! Most providers serve their metadata at the same link-local address (`169.254.169.254`), and some even at the same
+//! path (DigitalOcean and UpCloud both use `/metadata/v1.json`). Their metadata server checks are told apart by the
+//! response content alone: each checks for fields or values only its own metadata server serves, so a host matches
+//! at most one of them however the checks race.
 
-#[cfg(feature = "akami")]
+#[cfg(feature = "akamai")]
 pub(crate) mod akamai;
 #[cfg(feature = "alibaba")]
 pub(crate) mod alibaba;
@@ -8,13 +13,40 @@ pub(crate) mod alibaba;
 pub(crate) mod aws;
 #[cfg(feature = "azure")]
 pub(crate) mod azure;
+// Not every helper is used by every combination of provider features
+#[cfg(feature = "civo")]
+pub(crate) mod civo;
+#[allow(dead_code)]
+pub(crate) mod common;
 #[cfg(feature = "digitalocean")]
 pub(crate) mod digitalocean;
+#[cfg(feature = "equinix")]
+pub(crate) mod equinix;
+#[cfg(feature = "exoscale")]
+pub(crate) mod exoscale;
+#[cfg(feature = "fly")]
+pub(crate) mod fly;
 #[cfg(feature = "gcp")]
 pub(crate) mod gcp;
+#[cfg(feature = "heroku")]
+pub(crate) mod heroku;
+#[cfg(feature = "ibm")]
+pub(crate) mod ibm;
+#[cfg(feature = "kubernetes")]
+pub(crate) mod kubernetes;
+#[cfg(feature = "nutanix")]
+pub(crate) mod nutanix;
 #[cfg(feature = "oci")]
 pub(crate) mod oci;
 #[cfg(feature = "openstack")]
 pub(crate) mod openstack;
+#[cfg(feature = "ovh")]
+pub(crate) mod ovh;
+#[cfg(feature = "render")]
+pub(crate) mod render;
+#[cfg(feature = "upcloud")]
+pub(crate) mod upcloud;
+#[cfg(feature = "vmware")]
+pub(crate) mod vmware;
 #[cfg(feature = "vultr")]
 pub(crate) mod vultr;
@@ -0,0 +1,112 @@
+//! Nutanix.
+//!
+//! Nutanix AHV runs on-premises, so there is no metadata server at a well-known address to query. Instead, VMs are
+//! identified solely by their vendor files, which AHV fills in with its own name.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const SYS_VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const VENDOR: &str = "Nutanix";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Nutanix;
+
+pub(crate) struct Nutanix;
+
+#[async_trait]
+impl Provider for Nutanix {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Nutanix using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Nutanix");
+        if !ctx.vendor_files_enabled() {
+            return;
+        }
+
+        if self
+            .check_vendor_file(ctx.vendor_path(SYS_VENDOR_FILE))
+            .await
+            || self
+                .check_vendor_file(ctx.vendor_path(PRODUCT_NAME_FILE))
+                .await
+        {
+            let method = DetectionMethod::VendorFile;
+            tracing::trace!("Identified Nutanix using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[SYS_VENDOR_FILE, PRODUCT_NAME_FILE])
+    }
+}
+
+impl Nutanix {
+    /// Tries to identify Nutanix using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"Nutanix")?;
+
+        let provider = Nutanix;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"QEMU")?;
+
+        let provider = Nutanix;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identify_vendor_files_disabled() {
+        let provider = Nutanix;
+        let ctx = Context {
+            vendor_files: false,
+            ..Context::new(Client::new())
+        };
+        let (tx, mut rx) = mpsc::channel(1);
+        provider.identify(&ctx, tx).await;
+
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -1,17 +1,29 @@
 //! Oracle Cloud Infrastructure (OCI).
+//!
+//! Instance details such as the region and realm are served under `/opc/v2/instance/`, which requires an
+//! `Authorization: Bearer Oracle` header. Older images may only have the v1 endpoint (`/opc/v1/instance/`, without the
+//! header) enabled, so that is used as a fallback.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/opc/v1/instance/metadata/";
+const METADATA_ID_PATH: &str = "/opc/v1/instance/id";
+const METADATA_INSTANCE_PATH: &str = "/opc/v2/instance/";
+const METADATA_INSTANCE_V1_PATH: &str = "/opc/v1/instance/";
+const HEADERS: [(&str, &str); 1] = [("Authorization", "Bearer Oracle")];
 const VENDOR_FILE: &str = "/sys/class/dmi/id/chassis_asset_tag";
+pub(crate) const CHASSIS_ASSET_TAG: &str = "OracleCloud";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::OCI;
 
 #[derive(Serialize, Deserialize)]
@@ -20,6 +32,24 @@ struct MetadataResponse {
     oke_tm: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct InstanceResponse {
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(rename = "canonicalRegionName", default)]
+    canonical_region_name: Option<String>,
+    #[serde(rename = "ociAdName", default)]
+    oci_ad_name: Option<String>,
+    #[serde(rename = "regionInfo", default)]
+    region_info: Option<RegionInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegionInfo {
+    #[serde(rename = "realmKey")]
+    realm_key: String,
+}
+
 pub(crate) struct Oci;
 
 #[async_trait]
@@ -29,69 +59,130 @@ impl Provider for Oci {
     }
 
     /// Tries to identify OCI using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Oracle Cloud Infrastructure");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified Oracle Cloud Infrastructure");
-            let res = tx.send(IDENTIFIER).await;
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Oracle Cloud Infrastructure using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(
+            METADATA_URI,
+            &[
+                METADATA_PATH,
+                METADATA_ID_PATH,
+                METADATA_INSTANCE_PATH,
+                METADATA_INSTANCE_V1_PATH,
+            ],
+        )
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region (e.g. `us-ashburn-1`) from the instance metadata.
+    ///
+    /// Older instances report the full region name as `region`, while newer ones use a short code (e.g. `iad`) and
+    /// report the full name as `canonicalRegionName` instead.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        let instance = self
+            .get_instance::<InstanceResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?;
+
+        instance.canonical_region_name.or(instance.region)
+    }
+
+    /// Retrieves the availability domain (e.g. `iad-ad-1`) from the instance metadata.
+    async fn availability_zone(&self, ctx: &Context) -> Option<String> {
+        self.get_instance::<InstanceResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .oci_ad_name
+            .filter(|ad_name| !ad_name.is_empty())
+    }
+
+    /// Retrieves the realm (e.g. `oc1` for the commercial realm, or `oc2` for US Government) from the instance
+    /// metadata.
+    async fn environment(&self, ctx: &Context) -> Option<String> {
+        self.get_instance::<InstanceResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region_info
+            .map(|region_info| region_info.realm_key)
+            .filter(|realm| !realm.is_empty())
+    }
+
+    /// Retrieves the instance OCID from the metadata server.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        let url = format!("{}{METADATA_ID_PATH}", ctx.metadata_uri(METADATA_URI));
+        tracing::trace!("Retrieving {} instance ID using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .map(|text| text.trim().to_string())
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl Oci {
     /// Tries to identify OCI via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::check_metadata(ctx, IDENTIFIER, &url, &[], |metadata: MetadataResponse| {
+            metadata.oke_tm.contains("oke")
+        })
+        .await
+    }
+
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
         let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
-
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-
-        match client.get(url).send().await {
-            Ok(resp) => match resp.json::<MetadataResponse>().await {
-                Ok(resp) => resp.oke_tm.contains("oke"),
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
+
+    /// Retrieves the instance details from the metadata server, falling back to the v1 endpoint if v2 fails.
+    async fn get_instance<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_INSTANCE_PATH}");
+
+        match common::get_metadata(ctx, IDENTIFIER, &url, &HEADERS).await {
+            Some(instance) => Some(instance),
+            None => {
+                let url = format!("{metadata_uri}{METADATA_INSTANCE_V1_PATH}");
+
+                common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
             }
         }
     }
 
     /// Tries to identify OCI using vendor file(s).
     async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
-        tracing::trace!(
-            "Checking {} vendor file: {}",
-            IDENTIFIER,
-            vendor_file.as_ref().display()
-        );
-
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("OracleCloud"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
-
-        false
+        common::check_vendor_file(IDENTIFIER, vendor_file, CHASSIS_ASSET_TAG).await
     }
 }
 
@@ -100,8 +191,9 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
-    use wiremock::matchers::path;
+    use wiremock::matchers::{header, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
@@ -118,8 +210,9 @@ mod tests {
             .await;
 
         let provider = Oci;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -136,12 +229,105 @@ mod tests {
             .await;
 
         let provider = Oci;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_get_instance() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_INSTANCE_PATH))
+            .and(header("Authorization", "Bearer Oracle"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "region": "iad",
+                    "canonicalRegionName": "us-ashburn-1",
+                    "ociAdName": "iad-ad-1",
+                    "regionInfo": {"realmKey": "oc1", "regionIdentifier": "us-ashburn-1", "regionKey": "IAD"}
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Oci;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_instance::<InstanceResponse>(&ctx, &metadata_uri)
+            .await
+            .expect("instance");
+
+        assert_eq!(
+            result.canonical_region_name.as_deref(),
+            Some("us-ashburn-1")
+        );
+        assert_eq!(
+            result
+                .region_info
+                .map(|region_info| region_info.realm_key)
+                .as_deref(),
+            Some("oc1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_availability_zone() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_INSTANCE_PATH))
+            .and(header("Authorization", "Bearer Oracle"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "availabilityDomain": "Uocm:US-ASHBURN-AD-1",
+                    "canonicalRegionName": "us-ashburn-1",
+                    "ociAdName": "iad-ad-1",
+                    "region": "iad"
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Oci;
+        let config = crate::DetectConfig::new().metadata_base(mock_server.uri());
+        let ctx = Context::with_config(Client::new(), &config);
+        let result = provider.availability_zone(&ctx).await;
+
+        assert_eq!(result.as_deref(), Some("iad-ad-1"));
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_v1_fallback() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_INSTANCE_PATH))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path(METADATA_INSTANCE_V1_PATH))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"region": "us-phoenix-1"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Oci;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_instance::<InstanceResponse>(&ctx, &metadata_uri)
+            .await
+            .expect("instance");
+
+        assert_eq!(result.region.as_deref(), Some("us-phoenix-1"));
+        assert!(result.canonical_region_name.is_none());
+        assert!(result.region_info.is_none());
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut vendor_file = NamedTempFile::new()?;
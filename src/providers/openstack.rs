@@ -1,25 +1,55 @@
 //! OpenStack.
+//!
+//! Clouds built on OpenStack that have their own provider, such as OVHcloud, also match the checks here. When the
+//! more specific provider is enabled and matches, OpenStack defers to it rather than reporting a generic result.
+//!
+//! Deployments without a metadata service instead attach a config drive, a volume labelled `config-2` carrying the
+//! same metadata. Like the vendor files, it is read locally (wherever it is mounted), so it is checked alongside them
+//! and reported as a vendor file match.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::Value;
 use tokio::fs;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/openstack/";
+/// Metadata versions, in the order they are tried. Older deployments may not serve `latest`.
+const METADATA_VERSIONS: [&str; 10] = [
+    "latest",
+    "2020-10-14",
+    "2018-08-27",
+    "2017-02-22",
+    "2016-10-06",
+    "2016-06-30",
+    "2015-10-15",
+    "2013-10-17",
+    "2013-04-04",
+    "2012-08-10",
+];
 const PRODUCT_NAME_FILE: &str = "/sys/class/dmi/id/product_name";
-const PRODUCT_NAMES: [&str; 2] = ["Openstack Nova", "OpenStack Compute"];
+// Nova writes "OpenStack Nova", though some older deployments capitalised it as "Openstack Nova".
+pub(crate) const PRODUCT_NAMES: [&str; 3] =
+    ["OpenStack Nova", "Openstack Nova", "OpenStack Compute"];
 const CHASSIS_ASSET_TAG_FILE: &str = "/sys/class/dmi/id/chassis_asset_tag";
-const CHASSIS_ASSET_TAGS: [&str; 5] = [
+pub(crate) const CHASSIS_ASSET_TAGS: [&str; 5] = [
     "HUAWEICLOUD",
     "OpenTelekomCloud",
     "SAP CCloud VM",
     "OpenStack Nova",
     "OpenStack Compute",
 ];
+/// Link to the config drive's block device, which udev names after the volume label.
+const CONFIG_DRIVE_DEVICE: &str = "/dev/disk/by-label/config-2";
+const MOUNTS_FILE: &str = "/proc/mounts";
+/// Path of the instance metadata, relative to where the config drive is mounted.
+const CONFIG_DRIVE_METADATA_PATH: &str = "openstack/latest/meta_data.json";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::OpenStack;
 
 pub(crate) struct OpenStack;
@@ -31,44 +61,155 @@ impl Provider for OpenStack {
     }
 
     /// Tries to identify OpenStack using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        self.identify_with_config_drive(
+            ctx,
+            tx,
+            Path::new(CONFIG_DRIVE_DEVICE),
+            Path::new(MOUNTS_FILE),
+        )
+        .await
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        let instance_paths: Vec<String> =
+            METADATA_VERSIONS.iter().map(|v| instance_path(v)).collect();
+        let mut paths = vec![METADATA_PATH];
+        paths.extend(instance_paths.iter().map(String::as_str));
+
+        common::endpoints(METADATA_URI, &paths)
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[
+            PRODUCT_NAME_FILE,
+            CHASSIS_ASSET_TAG_FILE,
+            CONFIG_DRIVE_DEVICE,
+            MOUNTS_FILE,
+        ])
+    }
+
+    /// Retrieves the instance UUID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.instance_metadata(ctx)
+            .await?
+            .get("uuid")?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.instance_metadata(ctx).await
+    }
+}
+
+impl OpenStack {
+    /// Tries to identify OpenStack using all the implemented options, looking for the config drive at the given device.
+    async fn identify_with_config_drive(
+        &self,
+        ctx: &Context,
+        tx: Sender<Detection>,
+        config_drive_device: &Path,
+        mounts_file: &Path,
+    ) {
         tracing::trace!("Checking OpenStack");
-        if self
-            .check_vendor_files(PRODUCT_NAME_FILE, CHASSIS_ASSET_TAG_FILE)
-            .await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified OpenStack");
-            let res = tx.send(IDENTIFIER).await;
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            async {
+                self.check_vendor_files(
+                    ctx.vendor_path(PRODUCT_NAME_FILE),
+                    ctx.vendor_path(CHASSIS_ASSET_TAG_FILE),
+                )
+                .await
+                    || self
+                        .check_config_drive(config_drive_device, mounts_file)
+                        .await
+            },
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        #[cfg(feature = "ovh")]
+        if !methods.is_empty() && crate::providers::ovh::Ovh.is_ovh(ctx).await {
+            tracing::trace!("Deferring OpenStack to OVHcloud");
+            return;
+        }
+
+        for method in methods {
+            tracing::trace!("Identified OpenStack using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
-}
 
-impl OpenStack {
-    /// Tries to identify OpenStack via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
-        let timeout = crate::DEFAULT_DETECTION_TIMEOUT;
-        let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+    /// Retrieves the instance metadata from the metadata server, falling back to the config drive.
+    async fn instance_metadata(&self, ctx: &Context) -> Option<Value> {
+        if let Some(metadata) = self
+            .get_instance_metadata(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+        {
+            return Some(metadata);
+        }
+
+        let mount_point = find_config_drive(CONFIG_DRIVE_DEVICE, MOUNTS_FILE).await?;
+        read_config_drive(&mount_point).await
+    }
 
-        let client = if let Ok(client) = reqwest::Client::builder().timeout(timeout).build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
+    /// Tries to identify OpenStack via the instance metadata on a mounted config drive.
+    ///
+    /// The metadata must include the instance UUID, rather than the file merely existing.
+    async fn check_config_drive<P: AsRef<Path>>(&self, device: P, mounts_file: P) -> bool {
+        let Some(mount_point) = find_config_drive(device, mounts_file).await else {
             return false;
         };
 
-        match client.get(url).send().await {
-            Ok(resp) => resp.status().is_success(),
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
+        read_config_drive(&mount_point)
+            .await
+            .is_some_and(|metadata| metadata.get("uuid").is_some_and(Value::is_string))
+    }
+
+    /// Retrieves the instance metadata from the metadata server, using the first version that serves valid JSON.
+    async fn get_instance_metadata(&self, ctx: &Context, metadata_uri: &str) -> Option<Value> {
+        for version in METADATA_VERSIONS {
+            let url = format!("{metadata_uri}{}", instance_path(version));
+            tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+            match common::get(ctx, &url, &[]).send().await {
+                Ok(resp) if resp.status().is_success() => match resp.json().await {
+                    Ok(metadata) => return Some(metadata),
+                    Err(err) => tracing::trace!("Error reading response: {:?}", err),
+                },
+                Ok(resp) => tracing::trace!("Unexpected response status: {}", resp.status()),
+                Err(err) => {
+                    // The metadata server is unreachable, so other versions would fail the same way
+                    tracing::trace!("Error making request: {:?}", err);
+                    ctx.record_error(IDENTIFIER, &err);
+                    return None;
+                }
             }
         }
+
+        None
+    }
+
+    /// Tries to identify OpenStack via metadata server.
+    ///
+    /// The metadata server root lists the metadata versions it serves, one per line. Other providers' metadata servers
+    /// share the address, so the listing must name a known version rather than the server merely responding.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
+
+        common::send_text(ctx, IDENTIFIER, common::get(ctx, &url, &[]))
+            .await
+            .is_some_and(|text| {
+                text.lines()
+                    .any(|line| METADATA_VERSIONS.contains(&line.trim()))
+            })
     }
 
     /// Tries to identify OpenStack using vendor file(s).
@@ -77,74 +218,98 @@ impl OpenStack {
         product_name_file: P,
         chassis_asset_tag_file: P,
     ) -> bool {
-        tracing::trace!(
-            "Checking {} vendor file: {}",
-            IDENTIFIER,
-            product_name_file.as_ref().display()
-        );
-
-        if product_name_file.as_ref().is_file() {
-            match fs::read_to_string(product_name_file).await {
-                Ok(content) => {
-                    if PRODUCT_NAMES.iter().any(|&name| content.contains(name)) {
-                        return true;
-                    }
-                }
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                }
-            }
+        if common::read_vendor_file(IDENTIFIER, product_name_file)
+            .await
+            .is_some_and(|content| PRODUCT_NAMES.iter().any(|&name| content.contains(name)))
+        {
+            return true;
         }
 
-        tracing::trace!(
-            "Checking {} vendor file: {}",
-            IDENTIFIER,
-            chassis_asset_tag_file.as_ref().display(),
-        );
+        common::read_vendor_file(IDENTIFIER, chassis_asset_tag_file)
+            .await
+            .is_some_and(|content| {
+                CHASSIS_ASSET_TAGS
+                    .iter()
+                    .any(|&name| content.contains(name))
+            })
+    }
+}
 
-        if chassis_asset_tag_file.as_ref().is_file() {
-            match fs::read_to_string(chassis_asset_tag_file).await {
-                Ok(content) => {
-                    if CHASSIS_ASSET_TAGS
-                        .iter()
-                        .any(|&name| content.contains(name))
-                    {
-                        return true;
-                    }
-                }
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                }
-            }
-        }
+/// Finds where the config drive at the given device is mounted, according to the given mounts file.
+async fn find_config_drive<P: AsRef<Path>>(device: P, mounts_file: P) -> Option<PathBuf> {
+    tracing::trace!(
+        "Checking {} config drive: {}",
+        IDENTIFIER,
+        device.as_ref().display()
+    );
+
+    // The device is usually a link (e.g. to `/dev/sr0`), whereas the mounts file lists the device itself
+    let device = fs::canonicalize(device).await.ok()?;
+    let mounts = common::read_vendor_file(IDENTIFIER, mounts_file).await?;
+
+    mount_point(&device, &mounts)
+}
 
-        false
+/// Returns the mount point of the given device from the contents of a mounts file (e.g. `/proc/mounts`).
+fn mount_point(device: &Path, mounts: &str) -> Option<PathBuf> {
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let source = fields.next()?;
+        let target = fields.next()?;
+
+        // Spaces in mount points are escaped as `\040`
+        (Path::new(source) == device).then(|| PathBuf::from(target.replace("\\040", " ")))
+    })
+}
+
+/// Reads the instance metadata from the config drive mounted at the given path.
+async fn read_config_drive(mount_point: &Path) -> Option<Value> {
+    let metadata =
+        common::read_vendor_file(IDENTIFIER, mount_point.join(CONFIG_DRIVE_METADATA_PATH)).await?;
+
+    match serde_json::from_str(&metadata) {
+        Ok(metadata) => Some(metadata),
+        Err(err) => {
+            tracing::trace!("Error reading config drive metadata: {:?}", err);
+            None
+        }
     }
 }
 
+/// Returns the path of the instance metadata for the given version.
+fn instance_path(version: &str) -> String {
+    format!("/openstack/{version}/meta_data.json")
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
+    use tokio::sync::mpsc;
     use wiremock::matchers::path;
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
+    use crate::DetectionMethod;
 
     #[tokio::test]
     async fn test_check_metadata_server_success() {
         let mock_server = MockServer::start().await;
         Mock::given(path(METADATA_PATH))
-            .respond_with(ResponseTemplate::new(200))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("2012-08-10\n2013-04-04\nlatest\n"),
+            )
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = OpenStack;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -159,12 +324,229 @@ mod tests {
             .await;
 
         let provider = OpenStack;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_other_provider() {
+        // Other providers' metadata servers answer at the same address, but do not list OpenStack metadata versions
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"imageId": "ami-123abc", "instanceId": "i-123abc", "region": "us-east-1"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = OpenStack;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(!result);
     }
 
+    #[tokio::test]
+    async fn test_get_instance_metadata_version_fallback() {
+        let mock_server = MockServer::start().await;
+        // Unmocked paths, including `latest`, respond with 404
+        Mock::given(path("/openstack/2018-08-27/meta_data.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"uuid": "d8e02d56-2648"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = OpenStack;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.get_instance_metadata(&ctx, &metadata_uri).await;
+
+        assert_eq!(
+            result.and_then(|metadata| metadata.get("uuid").cloned()),
+            Some(Value::String("d8e02d56-2648".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_metadata_skips_invalid_json() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path("/openstack/latest/meta_data.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/openstack/2020-10-14/meta_data.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"uuid": "abc"}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = OpenStack;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.get_instance_metadata(&ctx, &metadata_uri).await;
+
+        assert!(result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_metadata_unreachable() {
+        let provider = OpenStack;
+        let ctx = Context::new(Client::new());
+        // Nothing listens on port 1, so only the first version is tried
+        let result = provider
+            .get_instance_metadata(&ctx, "http://127.0.0.1:1")
+            .await;
+
+        assert!(result.is_none());
+        assert_eq!(ctx.errors().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_check_config_drive() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let device = dir.path().join("sr0");
+        std::fs::write(&device, b"")?;
+        let label = dir.path().join("config-2");
+        std::os::unix::fs::symlink(&device, &label)?;
+
+        let mount_point = dir.path().join("config drive");
+        std::fs::create_dir_all(mount_point.join("openstack/latest"))?;
+        std::fs::write(
+            mount_point.join(CONFIG_DRIVE_METADATA_PATH),
+            r#"{"uuid": "83679162-1378-4288-a2d4-70e13ec132aa", "name": "test", "availability_zone": "nova"}"#,
+        )?;
+
+        let mut mounts_file = NamedTempFile::new()?;
+        writeln!(
+            mounts_file,
+            "proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n{} {} iso9660 ro,relatime 0 0",
+            device.canonicalize()?.display(),
+            mount_point.display().to_string().replace(' ', "\\040"),
+        )?;
+
+        let provider = OpenStack;
+        assert!(
+            provider
+                .check_config_drive(label.as_path(), mounts_file.path())
+                .await
+        );
+
+        // The config drive is not mounted
+        let empty_mounts_file = NamedTempFile::new()?;
+        assert!(
+            !provider
+                .check_config_drive(label.as_path(), empty_mounts_file.path())
+                .await
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_identify_config_drive_offline() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let device = dir.path().join("sr0");
+        std::fs::write(&device, b"")?;
+        let label = dir.path().join("config-2");
+        std::os::unix::fs::symlink(&device, &label)?;
+
+        let mount_point = dir.path().join("config-drive");
+        std::fs::create_dir_all(mount_point.join("openstack/latest"))?;
+        std::fs::write(
+            mount_point.join(CONFIG_DRIVE_METADATA_PATH),
+            r#"{"uuid": "83679162-1378-4288-a2d4-70e13ec132aa"}"#,
+        )?;
+
+        let mut mounts_file = NamedTempFile::new()?;
+        writeln!(
+            mounts_file,
+            "{} {} iso9660 ro,relatime 0 0",
+            device.canonicalize()?.display(),
+            mount_point.display(),
+        )?;
+
+        // The config drive is read locally, so it is still checked without the metadata server
+        let (tx, mut rx) = mpsc::channel(1);
+        let ctx = Context::offline(Client::new());
+        OpenStack
+            .identify_with_config_drive(&ctx, tx, &label, mounts_file.path())
+            .await;
+
+        let detection = rx.recv().await.expect("OpenStack should be identified");
+        assert_eq!(detection.id, IDENTIFIER);
+        assert_eq!(detection.method, DetectionMethod::VendorFile);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mount_point() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n/dev/sr0 /mnt/config iso9660 ro 0 0\n";
+
+        assert_eq!(
+            mount_point(Path::new("/dev/sr0"), mounts),
+            Some(PathBuf::from("/mnt/config"))
+        );
+        assert_eq!(mount_point(Path::new("/dev/vdb"), mounts), None);
+    }
+
+    #[cfg(feature = "ovh")]
+    #[tokio::test]
+    async fn test_identify_defers_to_ovh() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("latest\n"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/openstack/latest/meta_data.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"availability_zone": "nova", "meta": {"ovh-region": "GRA11"}}"#,
+            ))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(path("/openstack/latest/meta_data.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"availability_zone": "nova", "meta": {}}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Route requests for the real metadata server through the mock server
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all(mock_server.uri())?)
+            .build()?;
+        let ctx = Context::network_only(client);
+        let provider = OpenStack;
+
+        // OVHcloud markers present: leave the detection to the OVHcloud provider
+        let (tx, mut rx) = mpsc::channel(1);
+        provider.identify(&ctx, tx).await;
+        assert!(rx.recv().await.is_none());
+
+        // No OVHcloud markers: report generic OpenStack
+        let (tx, mut rx) = mpsc::channel(1);
+        provider.identify(&ctx, tx).await;
+        let detection = rx.recv().await.expect("detection");
+        assert_eq!(detection.id, IDENTIFIER);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_check_vendor_file_success() -> Result<()> {
         let mut product_name_file = NamedTempFile::new()?;
@@ -197,4 +579,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_check_vendor_files_product_name() -> Result<()> {
+        for product_name in PRODUCT_NAMES {
+            let mut product_name_file = NamedTempFile::new()?;
+            let chassis_asset_tag_file = NamedTempFile::new()?;
+
+            product_name_file.write_all(product_name.as_bytes())?;
+
+            let provider = OpenStack;
+            let result = provider
+                .check_vendor_files(product_name_file.path(), chassis_asset_tag_file.path())
+                .await;
+
+            assert!(result, "{product_name}");
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,225 @@
+//! OVHcloud.
+//!
+//! OVHcloud Public Cloud is built on OpenStack, so its instances also match the OpenStack provider. The OpenStack
+//! provider defers to this one whenever the OVHcloud-specific markers below are present.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/openstack/latest/meta_data.json";
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "OVH";
+const MARKER: &str = "ovh";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::OVH;
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    #[serde(default)]
+    availability_zone: String,
+    #[serde(default)]
+    meta: HashMap<String, String>,
+}
+
+impl MetadataResponse {
+    fn is_ovh(&self) -> bool {
+        let is_marker = |value: &str| value.to_lowercase().contains(MARKER);
+
+        is_marker(&self.availability_zone)
+            || self
+                .meta
+                .iter()
+                .any(|(key, value)| is_marker(key) || is_marker(value))
+    }
+}
+
+pub(crate) struct Ovh;
+
+#[async_trait]
+impl Provider for Ovh {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify OVHcloud using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking OVHcloud");
+        for method in self
+            .matches(
+                ctx,
+                ctx.vendor_path(VENDOR_FILE),
+                ctx.metadata_uri(METADATA_URI),
+            )
+            .await
+        {
+            tracing::trace!("Identified OVHcloud using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the instance UUID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<Value>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .get("uuid")?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+}
+
+impl Ovh {
+    /// Checks whether the host is an OVHcloud instance, for other providers that need to defer to it.
+    pub(crate) async fn is_ovh(&self, ctx: &Context) -> bool {
+        !self
+            .matches(
+                ctx,
+                ctx.vendor_path(VENDOR_FILE),
+                ctx.metadata_uri(METADATA_URI),
+            )
+            .await
+            .is_empty()
+    }
+
+    /// Tries to identify OVHcloud using the given vendor file and metadata server.
+    async fn matches<P: AsRef<Path>>(
+        &self,
+        ctx: &Context,
+        vendor_file: P,
+        metadata_uri: &str,
+    ) -> Vec<DetectionMethod> {
+        common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(vendor_file),
+            self.check_metadata_server(ctx, metadata_uri),
+        )
+        .await
+    }
+
+    /// Tries to identify OVHcloud via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        self.get_metadata::<MetadataResponse>(ctx, metadata_uri)
+            .await
+            .is_some_and(|metadata| metadata.is_ovh())
+    }
+
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
+
+    /// Tries to identify OVHcloud using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                availability_zone: "nova".to_string(),
+                meta: HashMap::from([("ovh-region".to_string(), "GRA11".to_string())]),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Ovh;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_failure() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
+                availability_zone: "nova".to_string(),
+                meta: HashMap::new(),
+            }))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Ovh;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"OVH SAS")?;
+
+        let provider = Ovh;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = Ovh;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
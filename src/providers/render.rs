@@ -0,0 +1,96 @@
+//! Render.
+//!
+//! Render services run in a managed environment without a recognizable vendor file or a metadata server. Instead,
+//! Render sets `RENDER` and `RENDER_*` environment variables for every service.
+
+use std::env;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const ENV_VARS: [&str; 2] = ["RENDER", "RENDER_SERVICE_ID"];
+const INSTANCE_ID_ENV_VAR: &str = "RENDER_INSTANCE_ID";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::Render;
+
+pub(crate) struct Render;
+
+#[async_trait]
+impl Provider for Render {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify Render using all the implemented options.
+    async fn identify(&self, _ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking Render");
+        if self.check_environment(&ENV_VARS) {
+            let method = DetectionMethod::Environment;
+            tracing::trace!("Identified Render using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    /// Retrieves the instance ID from the environment.
+    async fn instance_id(&self, _ctx: &Context) -> Option<String> {
+        env::var(INSTANCE_ID_ENV_VAR).ok()
+    }
+}
+
+impl Render {
+    /// Tries to identify Render using environment variable(s).
+    ///
+    /// `RENDER` alone is too generic a name to rely on, so every variable must be set.
+    fn check_environment(&self, env_vars: &[&str]) -> bool {
+        tracing::trace!(
+            "Checking {} environment variables: {:?}",
+            IDENTIFIER,
+            env_vars
+        );
+
+        env_vars
+            .iter()
+            .all(|var| env::var(var).is_ok_and(|value| !value.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_environment_success() {
+        // Use variables only this test touches, as tests share the process environment
+        env::set_var("CLOUD_DETECT_TEST_RENDER", "true");
+        env::set_var("CLOUD_DETECT_TEST_RENDER_SERVICE_ID", "srv-abc123");
+
+        let provider = Render;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_RENDER",
+            "CLOUD_DETECT_TEST_RENDER_SERVICE_ID",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_RENDER");
+        env::remove_var("CLOUD_DETECT_TEST_RENDER_SERVICE_ID");
+        assert!(result);
+    }
+
+    #[test]
+    fn test_check_environment_failure() {
+        env::set_var("CLOUD_DETECT_TEST_RENDER_ONLY", "true");
+
+        let provider = Render;
+        let result = provider.check_environment(&[
+            "CLOUD_DETECT_TEST_RENDER_ONLY",
+            "CLOUD_DETECT_TEST_RENDER_UNSET",
+        ]);
+
+        env::remove_var("CLOUD_DETECT_TEST_RENDER_ONLY");
+        assert!(!result);
+    }
+}
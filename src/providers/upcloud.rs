@@ -0,0 +1,248 @@
+//! UpCloud.
+//!
+//! UpCloud serves its metadata at the same path as DigitalOcean (`/metadata/v1.json`), so a reachable endpoint says
+//! nothing about the provider. Instead, the metadata is only trusted if it names UpCloud in its `cloud_name` field.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
+
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
+const METADATA_PATH: &str = "/metadata/v1.json";
+const CLOUD_NAME: &str = "upcloud";
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "UpCloud";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::UpCloud;
+
+pub(crate) struct UpCloud;
+
+#[derive(Serialize, Deserialize)]
+struct MetadataResponse {
+    cloud_name: String,
+    #[serde(default)]
+    instance_id: Option<String>,
+    #[serde(default)]
+    region: Option<String>,
+}
+
+#[async_trait]
+impl Provider for UpCloud {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify UpCloud using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking UpCloud");
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified UpCloud using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the zone (e.g. `fi-hel1`) from the server metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region
+    }
+
+    /// Retrieves the server UUID from the server metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .instance_id
+    }
+
+    /// Retrieves the raw server metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
+}
+
+impl UpCloud {
+    /// Tries to identify UpCloud via metadata server.
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::check_metadata(ctx, IDENTIFIER, &url, &[], |metadata: MetadataResponse| {
+            metadata.cloud_name == CLOUD_NAME
+        })
+        .await
+    }
+
+    /// Retrieves the server metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
+
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
+
+    /// Tries to identify UpCloud using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_metadata_server_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "cloud_name": "upcloud",
+                    "instance_id": "00133099-f1fd-4ed2-b1c7-d027eb43a8f5",
+                    "hostname": "upcloud-guest",
+                    "platform": "servers",
+                    "region": "fi-hel1"
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = UpCloud;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_digitalocean() {
+        // DigitalOcean serves its droplet metadata at the same path, without a `cloud_name`
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"droplet_id": 2756294, "hostname": "sample-droplet", "region": "nyc3"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = UpCloud;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_other_cloud_name() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"cloud_name": "othercloud"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = UpCloud;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "cloud_name": "upcloud",
+                    "instance_id": "00133099-f1fd-4ed2-b1c7-d027eb43a8f5",
+                    "region": "fi-hel1"
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = UpCloud;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await
+            .expect("metadata");
+
+        assert_eq!(
+            result.instance_id.as_deref(),
+            Some("00133099-f1fd-4ed2-b1c7-d027eb43a8f5")
+        );
+        assert_eq!(result.region.as_deref(), Some("fi-hel1"));
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"UpCloud")?;
+
+        let provider = UpCloud;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let vendor_file = NamedTempFile::new()?;
+
+        let provider = UpCloud;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! VMware vSphere, including VMware Cloud on AWS.
+//!
+//! Plain vSphere has no cloud metadata server, so VMs are identified solely by the system vendor file. As a more
+//! specific cloud may run on top of VMware (e.g. VMware Cloud on AWS), [detect_all](crate::detect_all) lists VMware
+//! after any other provider that matches.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+
+use crate::providers::common;
+use crate::{Context, Detection, DetectionMethod, Provider, ProviderId};
+
+const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "VMware";
+pub(crate) const IDENTIFIER: ProviderId = ProviderId::VMware;
+
+pub(crate) struct VMware;
+
+#[async_trait]
+impl Provider for VMware {
+    fn identifier(&self) -> ProviderId {
+        IDENTIFIER
+    }
+
+    /// Tries to identify VMware using all the implemented options.
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
+        tracing::trace!("Checking VMware");
+        if !ctx.vendor_files_enabled() {
+            return;
+        }
+
+        if self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)).await {
+            let method = DetectionMethod::VendorFile;
+            tracing::trace!("Identified VMware using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
+
+            if let Err(err) = res {
+                tracing::trace!("Error sending message: {:?}", err);
+            }
+        }
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+}
+
+impl VMware {
+    /// Tries to identify VMware using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anyhow::Result;
+    use reqwest::Client;
+    use tempfile::NamedTempFile;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_vendor_file_success() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"VMware, Inc.")?;
+
+        let provider = VMware;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_vendor_file_failure() -> Result<()> {
+        let mut vendor_file = NamedTempFile::new()?;
+        vendor_file.write_all(b"QEMU")?;
+
+        let provider = VMware;
+        let result = provider.check_vendor_file(vendor_file.path()).await;
+
+        assert!(!result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identify_vendor_files_disabled() {
+        let provider = VMware;
+        let ctx = Context {
+            vendor_files: false,
+            ..Context::new(Client::new())
+        };
+        let (tx, mut rx) = mpsc::channel(1);
+        provider.identify(&ctx, tx).await;
+
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -1,17 +1,21 @@
 //! Vultr.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 
-use crate::{Provider, ProviderId};
+use crate::providers::common;
+use crate::{Context, Detection, Provider, ProviderId};
 
-const METADATA_URI: &str = "http://169.254.169.254";
+pub(crate) const METADATA_URI: &str = "http://169.254.169.254";
 const METADATA_PATH: &str = "/v1.json";
 const VENDOR_FILE: &str = "/sys/class/dmi/id/sys_vendor";
+pub(crate) const SYS_VENDOR: &str = "Vultr";
 pub(crate) const IDENTIFIER: ProviderId = ProviderId::Vultr;
 
 pub(crate) struct Vultr;
@@ -20,6 +24,14 @@ pub(crate) struct Vultr;
 struct MetadataResponse {
     #[serde(rename = "instanceid")]
     instance_id: String,
+    #[serde(default)]
+    region: Option<Region>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Region {
+    #[serde(rename = "regioncode")]
+    region_code: String,
 }
 
 #[async_trait]
@@ -29,68 +41,79 @@ impl Provider for Vultr {
     }
 
     /// Tries to identify Vultr using all the implemented options.
-    async fn identify(&self, tx: Sender<ProviderId>) {
+    async fn identify(&self, ctx: &Context, tx: Sender<Detection>) {
         tracing::trace!("Checking Vultr");
-        if self.check_vendor_file(VENDOR_FILE).await
-            || self.check_metadata_server(METADATA_URI).await
-        {
-            tracing::trace!("Identified Vultr");
-            let res = tx.send(IDENTIFIER).await;
+        let methods = common::check_vendor_file_and_metadata(
+            ctx,
+            self.check_vendor_file(ctx.vendor_path(VENDOR_FILE)),
+            self.check_metadata_server(ctx, ctx.metadata_uri(METADATA_URI)),
+        )
+        .await;
+
+        for method in methods {
+            tracing::trace!("Identified Vultr using {}", method);
+            let res = tx.send(Detection::new(IDENTIFIER, method)).await;
 
             if let Err(err) = res {
                 tracing::trace!("Error sending message: {:?}", err);
             }
         }
     }
+
+    fn metadata_endpoints(&self) -> Vec<Url> {
+        common::endpoints(METADATA_URI, &[METADATA_PATH])
+    }
+
+    fn vendor_files(&self) -> Vec<PathBuf> {
+        common::paths(&[VENDOR_FILE])
+    }
+
+    /// Retrieves the region code (e.g. `EWR`) from the instance metadata.
+    async fn region(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await?
+            .region
+            .map(|region| region.region_code)
+    }
+
+    /// Retrieves the instance ID from the instance metadata.
+    async fn instance_id(&self, ctx: &Context) -> Option<String> {
+        self.get_metadata::<MetadataResponse>(ctx, ctx.metadata_uri(METADATA_URI))
+            .await
+            .map(|metadata| metadata.instance_id)
+    }
+
+    /// Retrieves the raw instance metadata.
+    async fn raw_metadata(&self, ctx: &Context) -> Option<Value> {
+        self.get_metadata(ctx, ctx.metadata_uri(METADATA_URI)).await
+    }
 }
 
 impl Vultr {
     /// Tries to identify Vultr via metadata server.
-    async fn check_metadata_server(&self, metadata_uri: &str) -> bool {
+    async fn check_metadata_server(&self, ctx: &Context, metadata_uri: &str) -> bool {
         let url = format!("{metadata_uri}{METADATA_PATH}");
-        tracing::trace!("Checking {} metadata using url: {}", IDENTIFIER, url);
-
-        let client = if let Ok(client) = reqwest::Client::builder().build() {
-            client
-        } else {
-            tracing::trace!("Error creating client");
-            return false;
-        };
-
-        match client.get(url).send().await {
-            Ok(resp) => match resp.json::<MetadataResponse>().await {
-                Ok(resp) => !resp.instance_id.is_empty(),
-                Err(err) => {
-                    tracing::trace!("Error reading response: {:?}", err);
-                    false
-                }
-            },
-            Err(err) => {
-                tracing::trace!("Error making request: {:?}", err);
-                false
-            }
-        }
+
+        common::check_metadata(ctx, IDENTIFIER, &url, &[], |metadata: MetadataResponse| {
+            !metadata.instance_id.is_empty()
+        })
+        .await
     }
 
-    /// Tries to identify Vultr using vendor file(s).
-    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
-        tracing::trace!(
-            "Checking {} vendor file: {}",
-            IDENTIFIER,
-            vendor_file.as_ref().display()
-        );
+    /// Retrieves the instance metadata from the metadata server.
+    async fn get_metadata<T: DeserializeOwned>(
+        &self,
+        ctx: &Context,
+        metadata_uri: &str,
+    ) -> Option<T> {
+        let url = format!("{metadata_uri}{METADATA_PATH}");
 
-        if vendor_file.as_ref().is_file() {
-            return match fs::read_to_string(vendor_file).await {
-                Ok(content) => content.contains("Vultr"),
-                Err(err) => {
-                    tracing::trace!("Error reading file: {:?}", err);
-                    false
-                }
-            };
-        }
+        common::get_metadata(ctx, IDENTIFIER, &url, &[]).await
+    }
 
-        false
+    /// Tries to identify Vultr using vendor file(s).
+    async fn check_vendor_file<P: AsRef<Path>>(&self, vendor_file: P) -> bool {
+        common::check_vendor_file(IDENTIFIER, vendor_file, SYS_VENDOR).await
     }
 }
 
@@ -99,6 +122,7 @@ mod tests {
     use std::io::Write;
 
     use anyhow::Result;
+    use reqwest::Client;
     use tempfile::NamedTempFile;
     use wiremock::matchers::path;
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -111,14 +135,16 @@ mod tests {
         Mock::given(path(METADATA_PATH))
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 instance_id: "i-123abc".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Vultr;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
 
         assert!(result);
     }
@@ -129,16 +155,63 @@ mod tests {
         Mock::given(path(METADATA_PATH))
             .respond_with(ResponseTemplate::new(200).set_body_json(MetadataResponse {
                 instance_id: "".to_string(),
+                region: None,
             }))
             .expect(1)
             .mount(&mock_server)
             .await;
 
         let provider = Vultr;
+        let ctx = Context::new(Client::new());
+        let metadata_uri = mock_server.uri();
+        let result = provider.check_metadata_server(&ctx, &metadata_uri).await;
+
+        assert!(!result);
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata() {
+        let mock_server = MockServer::start().await;
+        Mock::given(path(METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "hostname": "vultr-guest",
+                    "instanceid": "a747bfz6385e",
+                    "instance-v2-id": "a4c1ac4d-5c8e-4235-8ad5-f1e5e6f5a0c9",
+                    "region": {"regioncode": "EWR"}
+                }"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = Vultr;
+        let ctx = Context::new(Client::new());
         let metadata_uri = mock_server.uri();
-        let result = provider.check_metadata_server(&metadata_uri).await;
+        let result = provider
+            .get_metadata::<MetadataResponse>(&ctx, &metadata_uri)
+            .await
+            .expect("metadata");
+
+        assert_eq!(result.instance_id, "a747bfz6385e");
+        assert_eq!(
+            result.region.map(|region| region.region_code).as_deref(),
+            Some("EWR")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_metadata_server_records_error() {
+        let provider = Vultr;
+        let ctx = Context::new(Client::new());
+        // Nothing listens on port 1, so the request fails rather than returning a negative answer
+        let result = provider
+            .check_metadata_server(&ctx, "http://127.0.0.1:1")
+            .await;
 
         assert!(!result);
+        assert_eq!(ctx.errors().len(), 1);
+        assert_eq!(ctx.errors()[0].0, IDENTIFIER);
     }
 
     #[tokio::test]
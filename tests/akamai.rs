@@ -0,0 +1,12 @@
+//! Checks that the `akamai` feature alone enables the Akamai provider.
+//!
+//! Run with `cargo test --features akamai --test akamai`.
+
+#![cfg(feature = "akamai")]
+
+#[tokio::test]
+async fn test_supported_providers() {
+    let providers = cloud_detect::supported_providers().await;
+
+    assert!(providers.contains(&"akamai".to_string()));
+}
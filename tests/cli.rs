@@ -0,0 +1,57 @@
+//! Checks the `cloud-detect` command-line tool.
+//!
+//! Run with `cargo test --features cli --test cli`.
+
+#![cfg(feature = "cli")]
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+#[test]
+fn test_offline_json() {
+    let output = Command::cargo_bin("cloud-detect")
+        .unwrap()
+        .args(["--offline", "--json", "--timeout", "1"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result["provider"].is_string());
+}
+
+#[test]
+fn test_all_json() {
+    let output = Command::cargo_bin("cloud-detect")
+        .unwrap()
+        .args(["--all", "--json", "--timeout", "1"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result["providers"].is_array());
+}
+
+#[test]
+fn test_offline_conflicts_with_all() {
+    Command::cargo_bin("cloud-detect")
+        .unwrap()
+        .args(["--offline", "--all"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_invalid_timeout() {
+    Command::cargo_bin("cloud-detect")
+        .unwrap()
+        .args(["--timeout", "soon"])
+        .assert()
+        .failure();
+    Command::cargo_bin("cloud-detect")
+        .unwrap()
+        .args(["--timeout", "-1"])
+        .assert()
+        .failure();
+}
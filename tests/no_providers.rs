@@ -0,0 +1,51 @@
+//! Checks that building without any provider features yields no providers, with detection finding nothing.
+//!
+//! Run with `cargo test --no-default-features --test no_providers`.
+
+#![cfg(not(any(
+    feature = "akamai",
+    feature = "alibaba",
+    feature = "aws",
+    feature = "azure",
+    feature = "civo",
+    feature = "digitalocean",
+    feature = "equinix",
+    feature = "exoscale",
+    feature = "fly",
+    feature = "gcp",
+    feature = "heroku",
+    feature = "ibm",
+    feature = "kubernetes",
+    feature = "nutanix",
+    feature = "oci",
+    feature = "openstack",
+    feature = "ovh",
+    feature = "render",
+    feature = "upcloud",
+    feature = "vmware",
+    feature = "vultr"
+)))]
+
+use std::time::Duration;
+
+use cloud_detect::{
+    default_providers,
+    detect_with_timeout,
+    supported_providers,
+    ProviderId,
+    SUPPORTED_PROVIDERS,
+};
+
+#[tokio::test]
+async fn test_supported_providers() {
+    assert!(supported_providers().await.is_empty());
+    assert!(SUPPORTED_PROVIDERS.is_empty());
+    assert!(default_providers().is_empty());
+}
+
+#[tokio::test]
+async fn test_detect() {
+    let result = detect_with_timeout(Duration::from_secs(1)).await;
+
+    assert_eq!(result, Some(ProviderId::Unknown));
+}
@@ -0,0 +1,71 @@
+//! Checks that providers sharing the link-local metadata address only match their own metadata.
+//!
+//! Run with `cargo test --all-features --test shared_metadata_ip`.
+
+#![cfg(all(
+    feature = "akamai",
+    feature = "alibaba",
+    feature = "aws",
+    feature = "azure",
+    feature = "civo",
+    feature = "digitalocean",
+    feature = "exoscale",
+    feature = "ibm",
+    feature = "oci",
+    feature = "openstack",
+    feature = "ovh",
+    feature = "upcloud",
+    feature = "vultr"
+))]
+
+use std::time::Duration;
+
+use cloud_detect::{
+    default_providers,
+    detect_with_providers,
+    metadata_base_uri,
+    ProviderId,
+    METADATA_BASE_ENV_VAR,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SHARED_METADATA_URI: &str = "http://169.254.169.254";
+
+#[tokio::test]
+async fn test_aws_body_only_matches_aws() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/latest/api/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("123abc"))
+        .mount(&mock_server)
+        .await;
+    // Answer every other request with the instance identity document, as a server ignoring the path would
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"imageId": "ami-123abc", "instanceId": "i-123abc", "region": "us-east-1"}"#,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    // Only this test runs in this binary, so overriding the metadata server cannot leak into others
+    std::env::set_var(METADATA_BASE_ENV_VAR, mock_server.uri());
+
+    let shared: Vec<_> = default_providers()
+        .into_iter()
+        .filter(|p| metadata_base_uri(p.identifier()) == Some(SHARED_METADATA_URI))
+        .collect();
+    assert!(shared.len() > 1);
+
+    // Probe providers one at a time, so that a false match cannot hide behind the real one winning the race
+    for provider in shared {
+        let id = provider.identifier();
+        let detected = detect_with_providers(vec![provider], Some(Duration::from_secs(2))).await;
+
+        if id == ProviderId::AWS {
+            assert_eq!(detected, ProviderId::AWS);
+        } else {
+            assert_eq!(detected, ProviderId::Unknown, "{id} matched an AWS body");
+        }
+    }
+}